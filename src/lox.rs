@@ -1,67 +1,797 @@
+mod ast;
+mod class;
+mod cli;
+mod diagnostic;
+mod environment;
+mod function;
+mod interpreter;
+mod native;
+mod parser;
+mod printer;
+mod resolver;
 mod scanner;
+mod timings;
 mod token;
+mod value;
 
-use crate::lox::scanner::Scanner;
-use std::io::Write;
-use std::{env, fs, io, process};
+pub use crate::lox::diagnostic::{Diagnostic, Severity};
+pub use crate::lox::interpreter::InterpreterOptions;
+pub use crate::lox::scanner::{Scanner, ScannerOptions, keywords};
+pub use crate::lox::token::{Literal, Token, TokenKind};
 
-pub struct Lox {
+use crate::lox::ast::Stmt;
+use crate::lox::cli::{CliArgs, CliCommand, Mode};
+use crate::lox::interpreter::Interpreter;
+use crate::lox::native::Console;
+use crate::lox::parser::Parser;
+use crate::lox::printer::AstPrinter;
+use crate::lox::resolver::{Locals, Resolver};
+use crate::lox::timings::Timings;
+use std::io::{BufRead, Read, Stdout, Write};
+use std::time::Instant;
+use std::{env, fs, io, panic, process, thread};
+
+// Enough for `MAX_DEPTH` calls even in debug builds.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+// The call depth the command line allows on that stack.
+const MAX_DEPTH: usize = 1000;
+
+// `W` receives the output of `print` statements.
+pub struct Lox<W = Stdout>
+where
+    W: Write,
+{
     has_error: bool,
+    had_runtime_error: bool,
+    errors: Vec<Diagnostic>,
+    scanner_options: ScannerOptions,
+    interpreter: Interpreter<W>,
 }
 
-trait Reporter {
-    fn error(&mut self, line: u32, msg: &str) {
-        self.report(line, "", msg);
+// Receives diagnostics from the scanner, parser and resolver.
+pub trait Reporter {
+    fn error(&mut self, line: u32, column: u32, msg: &str) {
+        self.report(line, column, "", msg);
     }
 
-    fn report(&mut self, line: u32, info: &str, msg: &str);
+    fn report(&mut self, line: u32, column: u32, info: &str, msg: &str);
+
+    fn runtime_error(&mut self, line: u32, msg: &str) {
+        self.report_runtime(line, msg);
+    }
+
+    fn report_runtime(&mut self, line: u32, msg: &str);
+
+    // Warnings never stop the program from running.
+    fn warning(&mut self, line: u32, column: u32, msg: &str);
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Lox::new()
+    }
 }
 
 impl Lox {
+    pub fn new() -> Self {
+        Lox::with_output(io::stdout())
+    }
+
+    // Runs a whole program, printing to stdout, and returns every compile or
+    // runtime error instead of writing them out.
+    pub fn run_string(source: &str) -> Result<(), Vec<Diagnostic>> {
+        Lox::new().run_collecting(source)
+    }
+
     pub fn start() {
-        let mut lox = Lox { has_error: false };
-
-        let args: Vec<String> = env::args().collect();
-        match args.len() {
-            0..3 => lox.run_prompt(),
-            3 => lox.run_file(&args[2]),
-            _ => {
-                println!("cargo run -- path/to/**/*.lox")
+        // Deep Lox recursion needs more room than the main thread gets.
+        let main = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(Lox::main)
+            .expect("Failed to spawn the interpreter thread.");
+        if let Err(panic) = main.join() {
+            panic::resume_unwind(panic);
+        }
+    }
+
+    fn main() {
+        let mut lox = Lox::new().with_options(InterpreterOptions {
+            max_depth: MAX_DEPTH,
+            ..InterpreterOptions::default()
+        });
+
+        let args: Vec<String> = env::args().skip(1).collect();
+        let CliCommand::Start(CliArgs { mode, path, time }) = cli::parse_args(&args) else {
+            eprintln!("{}", cli::USAGE);
+            process::exit(64);
+        };
+
+        let result = match (mode, path.as_deref()) {
+            (Mode::Run, None) => {
+                lox.run_prompt();
+                Ok(())
             }
+            (Mode::Run, Some(path)) => lox.run_file(path, time),
+            (Mode::Tokens, path) => lox.dump_tokens(path),
+            (Mode::TokensJson, path) => lox.dump_tokens_json(path),
+            (Mode::Ast, path) => lox.dump_ast(path),
+            (Mode::Check, path) => lox.check_file(path),
+        };
+
+        if let Err(error) = result {
+            let path = path.as_deref().unwrap_or("<stdin>");
+            eprintln!("Could not read file '{path}': {error}");
+            process::exit(66);
+        }
+        // The REPL resets both flags after every line, so it always exits 0.
+        let code = exit_code(lox.has_error, lox.had_runtime_error);
+        if code != 0 {
+            process::exit(code);
         }
     }
 
     fn run_prompt(&mut self) {
-        loop {
-            print!("> ");
-            io::stdout().flush().unwrap();
+        self.prompt_loop(&mut io::stdout());
+    }
 
-            let mut content = String::new();
-            io::stdin()
-                .read_line(&mut content)
-                .expect("Failed to read line");
+    fn run_file(&mut self, file_path: &str, time: bool) -> io::Result<()> {
+        let content = fs::read_to_string(file_path)?;
+        let timings = self.run_with_timing(&content);
+        self.write_errors(&content, &mut io::stderr());
+        if time {
+            eprintln!("{timings}");
+        }
 
-            self.run(&content);
+        Ok(())
+    }
 
-            if self.has_error == true {
-                process::exit(65);
+    fn dump_tokens(&mut self, file_path: Option<&str>) -> io::Result<()> {
+        let content = read_source(file_path)?;
+        self.write_tokens(&content, &mut io::stdout());
+        self.write_errors(&content, &mut io::stderr());
+
+        Ok(())
+    }
+
+    fn dump_tokens_json(&mut self, file_path: Option<&str>) -> io::Result<()> {
+        let content = read_source(file_path)?;
+        self.write_tokens_json(&content, &mut io::stdout());
+        self.write_errors(&content, &mut io::stderr());
+
+        Ok(())
+    }
+
+    fn dump_ast(&mut self, file_path: Option<&str>) -> io::Result<()> {
+        let content = read_source(file_path)?;
+        self.write_ast(&content, &mut io::stdout());
+        self.write_errors(&content, &mut io::stderr());
+
+        Ok(())
+    }
+
+    fn check_file(&mut self, file_path: Option<&str>) -> io::Result<()> {
+        let content = read_source(file_path)?;
+        self.check(&content);
+        self.write_errors(&content, &mut io::stderr());
+
+        Ok(())
+    }
+
+    fn write_tokens(&mut self, code: &str, out: &mut impl Write) {
+        for token in self.scanner(code).scan() {
+            writeln!(
+                out,
+                "{:?} '{}' {}:{}",
+                token.kind, token.lexeme, token.line, token.column
+            )
+            .unwrap();
+        }
+        out.flush().unwrap();
+    }
+
+    // One token object per line inside a JSON array.
+    fn write_tokens_json(&mut self, code: &str, out: &mut impl Write) {
+        let tokens: Vec<String> = self
+            .scanner(code)
+            .scan()
+            .iter()
+            .map(|token| format!("  {}", token.to_json()))
+            .collect();
+        writeln!(out, "[\n{}\n]", tokens.join(",\n")).unwrap();
+        out.flush().unwrap();
+    }
+
+    // Prints nothing unless the whole program parses.
+    fn write_ast(&mut self, code: &str, out: &mut impl Write) {
+        let tokens = self.scanner(code).scan();
+        if self.has_error {
+            return;
+        }
+
+        let statements = Parser::new(self, tokens).parse();
+        if self.has_error {
+            return;
+        }
+
+        for stmt in &statements {
+            writeln!(out, "{}", AstPrinter.print_stmt(stmt)).unwrap();
+        }
+        out.flush().unwrap();
+    }
+}
+
+impl Lox<Vec<u8>> {
+    // Like `run_string`, but also returns what the program printed.
+    pub fn run_string_captured(source: &str) -> (String, Result<(), Vec<Diagnostic>>) {
+        let mut lox = Lox::with_output(vec![]);
+        let result = lox.run_collecting(source);
+        let output = String::from_utf8_lossy(lox.interpreter.output()).into_owned();
+
+        (output, result)
+    }
+}
+
+impl<W> Lox<W>
+where
+    W: Write,
+{
+    fn with_output(out: W) -> Self {
+        Lox {
+            has_error: false,
+            had_runtime_error: false,
+            errors: vec![],
+            scanner_options: ScannerOptions::default(),
+            interpreter: Interpreter::with_output(out),
+        }
+    }
+
+    pub fn with_options(mut self, options: InterpreterOptions) -> Self {
+        self.interpreter.set_options(options);
+        self
+    }
+
+    pub fn with_scanner_options(mut self, options: ScannerOptions) -> Self {
+        self.scanner_options = options;
+        self
+    }
+
+    // Every phase scans through here so the scanner options always apply.
+    fn scanner<'a>(&'a mut self, code: &'a str) -> Scanner<'a, Self> {
+        let options = self.scanner_options.clone();
+        Scanner::with_options(self, code, options)
+    }
+
+    // Replaces stdin as the source of `input()` and REPL lines.
+    pub fn with_input(mut self, input: impl BufRead + 'static) -> Self {
+        self.interpreter.set_input(Box::new(input));
+        self
+    }
+
+    // Runs until EOF (Ctrl-D), then moves past the dangling prompt. Lines
+    // are buffered while brackets are open. Lines come from the
+    // interpreter's input, so `input()` reads from the same stream.
+    fn prompt_loop(&mut self, out: &mut impl Write) {
+        let mut buffer = String::new();
+        loop {
+            let prompt = if buffer.is_empty() { "> " } else { "... " };
+            write!(out, "{prompt}").unwrap();
+            out.flush().unwrap();
+
+            let Some(line) = self.interpreter.read_line().expect("Failed to read line") else {
+                writeln!(out).unwrap();
+                break;
+            };
+            buffer.push_str(&line);
+            if has_open_brackets(&buffer, &self.scanner_options) {
+                continue;
             }
+
+            self.repl_line(&buffer);
+            buffer.clear();
         }
     }
 
-    fn run_file(&mut self, file_path: &str) {
-        let content = fs::read_to_string(file_path).unwrap();
-        self.run(&content);
+    // Errors are reported but never end the session.
+    fn repl_line(&mut self, line: &str) {
+        self.run(line);
+        self.write_errors(line, &mut io::stderr());
+
+        self.has_error = false;
+        self.had_runtime_error = false;
+    }
+
+    // Like `run_string`, but keeps globals between calls. Warnings alone
+    // don't fail the run and stay queued for `take_errors`.
+    pub fn run_collecting(&mut self, source: &str) -> Result<(), Vec<Diagnostic>> {
+        self.has_error = false;
+        self.had_runtime_error = false;
+        self.run(source);
+
+        if self.errors.iter().any(Diagnostic::is_error) {
+            Err(self.take_errors())
+        } else {
+            Ok(())
+        }
     }
 
     fn run(&mut self, code: &str) {
-        Scanner::scan(self, code);
+        self.run_with_timing(code);
+    }
+
+    fn run_with_timing(&mut self, code: &str) -> Timings {
+        let mut timings = Timings::default();
+        let Some((statements, locals)) = self.compile(code, &mut timings) else {
+            return timings;
+        };
+        self.interpreter.resolve(locals);
+
+        let start = Instant::now();
+        let result = self.interpreter.interpret(&statements);
+        timings.interpret = start.elapsed();
+        if let Err(error) = result {
+            self.runtime_error(error.line, &error.msg);
+        }
+
+        timings
+    }
+
+    // Runs every phase but the interpreter, for `--check`.
+    fn check(&mut self, code: &str) {
+        self.compile(code, &mut Timings::default());
+    }
+
+    // Scans, parses and resolves, stopping after the first phase with errors.
+    fn compile(&mut self, code: &str, timings: &mut Timings) -> Option<(Vec<Stmt>, Locals)> {
+        let start = Instant::now();
+        let tokens = self.scanner(code).scan();
+        timings.scan = start.elapsed();
+        if self.has_error {
+            return None;
+        }
+
+        let start = Instant::now();
+        let statements = Parser::new(self, tokens).parse();
+        timings.parse = start.elapsed();
+        if self.has_error {
+            return None;
+        }
+
+        let start = Instant::now();
+        let locals = Resolver::new(self).resolve(&statements);
+        timings.resolve = start.elapsed();
+        if self.has_error {
+            return None;
+        }
+
+        Some((statements, locals))
+    }
+
+    // Every queued diagnostic, warnings included, in the order reported.
+    pub fn take_errors(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // Each diagnostic is followed by the offending source line.
+    fn write_errors(&mut self, source: &str, out: &mut impl Write) {
+        for error in self.take_errors() {
+            writeln!(out, "{error}").unwrap();
+            if let Some(snippet) = error.snippet(source) {
+                writeln!(out, "{snippet}").unwrap();
+            }
+        }
+        out.flush().unwrap();
+    }
+}
+
+// Compile errors exit 65 and runtime errors 70, as in the reference
+// interpreter.
+fn exit_code(has_error: bool, had_runtime_error: bool) -> i32 {
+    if has_error {
+        65
+    } else if had_runtime_error {
+        70
+    } else {
+        0
     }
 }
 
-impl Reporter for Lox {
-    fn report(&mut self, line: u32, info: &str, msg: &str) {
+// Counts unclosed `(` and `{` by token, so brackets in strings and comments
+// are ignored. Scan errors are left for the real run to report.
+fn has_open_brackets(source: &str, options: &ScannerOptions) -> bool {
+    let tokens = Scanner::with_options(&mut vec![], source, options.clone()).scan();
+    let mut depth = 0;
+    for token in tokens {
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::LeftBrace => depth += 1,
+            TokenKind::RightParen | TokenKind::RightBrace => depth -= 1,
+            _ => (),
+        }
+    }
+
+    depth > 0
+}
+
+// Reads the whole of stdin when no path is given.
+fn read_source(file_path: Option<&str>) -> io::Result<String> {
+    match file_path {
+        Some(file_path) => fs::read_to_string(file_path),
+        None => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            Ok(content)
+        }
+    }
+}
+
+// Collects diagnostics without touching any error flags.
+impl Reporter for Vec<Diagnostic> {
+    fn report(&mut self, line: u32, column: u32, info: &str, msg: &str) {
+        self.push(Diagnostic {
+            line,
+            column,
+            info: info.to_string(),
+            msg: msg.to_string(),
+            severity: Severity::Error,
+        });
+    }
+
+    fn report_runtime(&mut self, line: u32, msg: &str) {
+        self.push(Diagnostic::runtime(line, msg));
+    }
+
+    fn warning(&mut self, line: u32, column: u32, msg: &str) {
+        self.push(Diagnostic::warning(line, column, msg));
+    }
+}
+
+impl<W> Reporter for Lox<W>
+where
+    W: Write,
+{
+    fn report(&mut self, line: u32, column: u32, info: &str, msg: &str) {
         self.has_error = true;
-        println!("[line {line}] Error{info}: {msg}");
+        self.errors.push(Diagnostic {
+            line,
+            column,
+            info: info.to_string(),
+            msg: msg.to_string(),
+            severity: Severity::Error,
+        });
+    }
+
+    fn report_runtime(&mut self, line: u32, msg: &str) {
+        self.had_runtime_error = true;
+        self.errors.push(Diagnostic::runtime(line, msg));
+    }
+
+    fn warning(&mut self, line: u32, column: u32, msg: &str) {
+        self.errors.push(Diagnostic::warning(line, column, msg));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn run_valid_source() {
+        let mut lox = Lox::new();
+        lox.run("print (1 + 2) * -3; // comment");
+
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn run_reports_scan_error() {
+        let mut lox = Lox::new();
+        lox.run("var @ = 1;");
+
+        assert!(lox.has_error);
+    }
+
+    #[test]
+    fn run_collects_diagnostics() {
+        let mut lox = Lox::new();
+        lox.run("var @ = \"open;");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![
+                Diagnostic {
+                    line: 1,
+                    column: 5,
+                    info: String::new(),
+                    msg: "Unexpected character '@'.".to_string(),
+                    severity: Severity::Error,
+                },
+                Diagnostic {
+                    line: 1,
+                    column: 9,
+                    info: String::new(),
+                    msg: "Unterminated string.".to_string(),
+                    severity: Severity::Error,
+                },
+            ]
+        );
+        assert!(lox.take_errors().is_empty());
+    }
+
+    #[test]
+    fn write_errors_to_buffer() {
+        let mut lox = Lox::new();
+        lox.run("1 + @;");
+
+        let mut stderr = vec![];
+        lox.write_errors("1 + @;", &mut stderr);
+
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            "[line 1, column 5] Error: Unexpected character '@'.\n\
+             1 | 1 + @;\n\
+            \x20 |     ^\n"
+        );
+        assert!(lox.take_errors().is_empty());
+    }
+
+    #[test]
+    fn run_collecting_recovers_after_errors() {
+        let mut lox = Lox::with_output(vec![]);
+
+        assert!(lox.run_collecting("print ;").is_err());
+        assert!(lox.run_collecting("-nil;").is_err());
+        assert_eq!(lox.run_collecting("print 1;"), Ok(()));
+        assert_eq!(lox.interpreter.output(), b"1\n");
+    }
+
+    #[test]
+    fn warnings_are_written_with_errors() {
+        let source = "{ var a = 1; }\nprint 1;";
+        let mut lox = Lox::with_output(vec![]);
+
+        assert_eq!(lox.run_collecting(source), Ok(()));
+        assert!(!lox.has_error);
+        assert_eq!(lox.interpreter.output(), b"1\n");
+
+        let mut stderr = vec![];
+        lox.write_errors(source, &mut stderr);
+
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            "[line 1, column 7] Warning: Local variable 'a' is never used.\n\
+             1 | { var a = 1; }\n\
+            \x20 |       ^\n"
+        );
+        assert_eq!(exit_code(lox.has_error, lox.had_runtime_error), 0);
+    }
+
+    #[test]
+    fn runtime_error_sets_flag() {
+        let mut lox = Lox::new();
+        lox.runtime_error(1, "Operand must be a number.");
+
+        assert!(lox.had_runtime_error);
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(exit_code(false, false), 0);
+        assert_eq!(exit_code(true, false), 65);
+        assert_eq!(exit_code(false, true), 70);
+        assert_eq!(exit_code(true, true), 65);
+    }
+
+    #[test]
+    fn write_tokens_to_buffer() {
+        let mut lox = Lox::new();
+        let mut stdout = vec![];
+        lox.write_tokens("var a =\n  1;", &mut stdout);
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            "Var 'var' 1:1\n\
+             Identifier 'a' 1:5\n\
+             Equal '=' 1:7\n\
+             Number '1' 2:3\n\
+             Semicolon ';' 2:4\n\
+             Eof '' 2:5\n"
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn write_tokens_json_to_buffer() {
+        let mut lox = Lox::new();
+        let mut stdout = vec![];
+        lox.write_tokens_json("print \"a \\\"quote\\\"\";", &mut stdout);
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            r#"[
+  {"kind": "Print", "lexeme": "print", "line": 1, "column": 1},
+  {"kind": "String", "lexeme": "\"a \\\"quote\\\"\"", "line": 1, "column": 7},
+  {"kind": "Semicolon", "lexeme": ";", "line": 1, "column": 20},
+  {"kind": "Eof", "lexeme": "", "line": 1, "column": 21}
+]
+"#
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn write_ast_to_buffer() {
+        let mut lox = Lox::new();
+        let mut stdout = vec![];
+        lox.write_ast(
+            "var a = 1;\nfun f(x) { return x * 2; }\nif (a) print f(a);\n\
+             switch (a) { case 1: print 1; a = 2; default: }",
+            &mut stdout,
+        );
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            "(var a 1)\n\
+             (fun f (x) (return (* x 2)))\n\
+             (if a (print (call f a)))\n\
+             (switch a (case 1 (print 1) (; (= a 2))) (default))\n"
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn write_ast_skips_output_on_parse_error() {
+        let mut lox = Lox::new();
+        let mut stdout = vec![];
+        lox.write_ast("print 1;\nprint ;", &mut stdout);
+
+        assert!(stdout.is_empty());
+        assert!(lox.has_error);
+    }
+
+    #[test]
+    fn check_reports_errors_without_running() {
+        let mut lox = Lox::with_output(vec![]);
+        lox.check("print 1;\n{ var a = a; print a; }");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 2,
+                column: 11,
+                info: " at 'a'".to_string(),
+                msg: "Can't read local variable in its own initializer.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+        assert!(lox.interpreter.output().is_empty());
+        assert_eq!(exit_code(lox.has_error, lox.had_runtime_error), 65);
+
+        let mut lox = Lox::with_output(vec![]);
+        lox.check("print 1; print -nil;");
+
+        assert!(lox.interpreter.output().is_empty());
+        assert_eq!(exit_code(lox.has_error, lox.had_runtime_error), 0);
+    }
+
+    #[test]
+    fn repl_line_resets_errors() {
+        let mut lox = Lox::new();
+
+        lox.repl_line("var @;");
+        assert!(!lox.has_error);
+
+        lox.repl_line("-nil;");
+        assert!(!lox.had_runtime_error);
+
+        lox.repl_line("var a = 1;");
+        lox.repl_line("a = a + 1;");
+        assert!(!lox.has_error);
+        assert!(!lox.had_runtime_error);
+    }
+
+    #[test]
+    fn prompt_loop_stops_at_eof() {
+        let mut lox = Lox::new().with_input("var a = 1;\nvar b = a;\n".as_bytes());
+        let mut stdout = vec![];
+        lox.prompt_loop(&mut stdout);
+
+        assert_eq!(String::from_utf8(stdout).unwrap(), "> > > \n");
+    }
+
+    #[test]
+    fn prompt_loop_empty_input() {
+        let mut lox = Lox::new().with_input(io::empty());
+        let mut stdout = vec![];
+        lox.prompt_loop(&mut stdout);
+
+        assert_eq!(String::from_utf8(stdout).unwrap(), "> \n");
+    }
+
+    #[test]
+    fn prompt_loop_shares_input_with_programs() {
+        let input = "var name = input(\"name? \");\nAda\nprint name;\n";
+        let mut lox = Lox::with_output(vec![]).with_input(input.as_bytes());
+        let mut prompts = vec![];
+        lox.prompt_loop(&mut prompts);
+
+        assert_eq!(String::from_utf8(prompts).unwrap(), "> > > \n");
+        assert_eq!(
+            String::from_utf8_lossy(lox.interpreter.output()),
+            "name? Ada\n"
+        );
+    }
+
+    #[test]
+    fn run_missing_file() {
+        let mut lox = Lox::new();
+        let result = lox.run_file("does/not/exist.lox", false);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(!lox.has_error);
+        assert!(!lox.had_runtime_error);
+    }
+
+    #[test]
+    fn open_brackets() {
+        let options = ScannerOptions::default();
+        assert!(has_open_brackets("fun f() {\n", &options));
+        assert!(has_open_brackets("print (1 +\n", &options));
+        assert!(!has_open_brackets("fun f() {\n  return 1;\n}\n", &options));
+        assert!(!has_open_brackets("print \"{\"; // (\n", &options));
+        assert!(!has_open_brackets("}\n", &options));
+    }
+
+    #[test]
+    fn prompt_loop_uses_scanner_options() {
+        let options = ScannerOptions {
+            extra_keywords: HashMap::from([
+                ("begin", TokenKind::LeftBrace),
+                ("end", TokenKind::RightBrace),
+            ]),
+            ..ScannerOptions::default()
+        };
+        let mut lox = Lox::new()
+            .with_scanner_options(options)
+            .with_input("fun f() begin\n  return 2;\nend\nvar a = f();\n".as_bytes());
+        let mut stdout = vec![];
+        lox.prompt_loop(&mut stdout);
+
+        assert_eq!(String::from_utf8(stdout).unwrap(), "> ... ... > > \n");
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn prompt_loop_buffers_open_brackets() {
+        let mut lox = Lox::new().with_input("fun f() {\n  return 2;\n}\nvar a = f();\n".as_bytes());
+        let mut stdout = vec![];
+        lox.prompt_loop(&mut stdout);
+
+        assert_eq!(String::from_utf8(stdout).unwrap(), "> ... ... > > \n");
+        assert!(!lox.has_error);
+        assert_eq!(lox.run_collecting("a;"), Ok(()));
+    }
+
+    #[test]
+    fn run_with_timing_measures_phases() {
+        let mut lox = Lox::with_output(io::sink());
+        let timings = lox.run_with_timing("var i = 0; while (i < 1000) i = i + 1;");
+
+        assert!(timings.scan > Duration::ZERO);
+        assert!(timings.parse > Duration::ZERO);
+        assert!(timings.interpret > Duration::ZERO);
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn run_with_timing_skips_phases_after_errors() {
+        let mut lox = Lox::new();
+        let timings = lox.run_with_timing("print ;");
+
+        assert!(timings.scan > Duration::ZERO);
+        assert_eq!(timings.resolve, Duration::ZERO);
+        assert_eq!(timings.interpret, Duration::ZERO);
+        assert!(lox.has_error);
     }
 }
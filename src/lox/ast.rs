@@ -0,0 +1,129 @@
+use crate::lox::token::{Literal, Token};
+use std::rc::Rc;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Expr {
+    // `bracket` is the closing `]`, used to report errors.
+    ArrayLiteral {
+        bracket: Token,
+        elements: Vec<Expr>,
+    },
+    Assign {
+        name: Token,
+        value: Box<Expr>,
+    },
+    Binary {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+    },
+    Call {
+        callee: Box<Expr>,
+        paren: Token,
+        arguments: Vec<Expr>,
+    },
+    Get {
+        object: Box<Expr>,
+        name: Token,
+    },
+    Grouping(Box<Expr>),
+    // `++` or `--` on a variable, yielding the new value when prefixed.
+    Increment {
+        name: Token,
+        operator: Token,
+        prefix: bool,
+    },
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
+    IndexSet {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+    Lambda {
+        keyword: Token,
+        params: Vec<Token>,
+        body: Rc<[Stmt]>,
+    },
+    Literal(Literal),
+    Logical {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+    },
+    Set {
+        object: Box<Expr>,
+        name: Token,
+        value: Box<Expr>,
+    },
+    Super {
+        keyword: Token,
+        method: Token,
+    },
+    Ternary {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+    This(Token),
+    Unary {
+        operator: Token,
+        right: Box<Expr>,
+    },
+    Variable(Token),
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Stmt {
+    Block(Vec<Stmt>),
+    Break(Token),
+    Class {
+        name: Token,
+        superclass: Option<Expr>,
+        methods: Vec<Stmt>,
+    },
+    // Like `Var`, but the binding can't be assigned to afterwards.
+    Const {
+        name: Token,
+        initializer: Expr,
+    },
+    Continue(Token),
+    Expression(Expr),
+    Function {
+        name: Token,
+        params: Vec<Token>,
+        body: Rc<[Stmt]>,
+    },
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+    Print(Expr),
+    // Runs the first case equal to the value, or the default. Cases never
+    // fall through.
+    Switch {
+        value: Expr,
+        cases: Vec<(Expr, Vec<Stmt>)>,
+        default: Option<Vec<Stmt>>,
+    },
+    Return {
+        keyword: Token,
+        value: Option<Expr>,
+    },
+    Var {
+        name: Token,
+        initializer: Option<Expr>,
+    },
+    // `increment` is only set by desugared `for` loops, it runs after the
+    // body even when the body continues.
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+        increment: Option<Expr>,
+    },
+}
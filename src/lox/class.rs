@@ -0,0 +1,99 @@
+use crate::lox::function::Function;
+use crate::lox::interpreter::RuntimeError;
+use crate::lox::token::Token;
+use crate::lox::value::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: HashMap<String, Rc<Function>>,
+}
+
+impl Class {
+    pub fn find_method(&self, name: &str) -> Option<Rc<Function>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(method.clone());
+        }
+
+        self.superclass
+            .as_ref()
+            .and_then(|superclass| superclass.find_method(name))
+    }
+
+    pub fn arity(&self) -> usize {
+        self.find_method("init").map_or(0, |init| init.arity())
+    }
+}
+
+impl fmt::Debug for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Clone)]
+pub struct Instance {
+    pub class: Rc<Class>,
+    fields: HashMap<String, Value>,
+    // Field names in the order they were first assigned.
+    names: Vec<String>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: HashMap::new(),
+            names: vec![],
+        }
+    }
+
+    // Fields shadow methods of the same name. Takes the shared handle so
+    // methods can be bound to it.
+    pub fn get(instance: &Rc<RefCell<Instance>>, name: &Token) -> Result<Value, RuntimeError> {
+        let this = instance.borrow();
+        if let Some(value) = this.fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        match this.class.find_method(&name.lexeme) {
+            Some(method) => Ok(Value::Function(Rc::new(method.bind(instance.clone())))),
+            None => Err(RuntimeError::new(
+                name,
+                &format!("Undefined property '{}'.", name.lexeme),
+            )),
+        }
+    }
+
+    pub fn set(&mut self, name: &Token, value: Value) {
+        if self.fields.insert(name.lexeme.clone(), value).is_none() {
+            self.names.push(name.lexeme.clone());
+        }
+    }
+
+    pub fn field_names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+impl fmt::Debug for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<instance {}>", self.class.name)
+    }
+}
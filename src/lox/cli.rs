@@ -0,0 +1,148 @@
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Mode {
+    Run,
+    Tokens,
+    TokensJson,
+    Ast,
+    // Reports compile errors without running the program.
+    Check,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct CliArgs {
+    pub mode: Mode,
+    pub path: Option<String>,
+    // Report how long each phase of a file run took.
+    pub time: bool,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum CliCommand {
+    Start(CliArgs),
+    Usage,
+}
+
+pub const USAGE: &str =
+    "Usage: lox [--tokens | --tokens-json | --ast | --check] [script] | lox --time script";
+
+// Expects the arguments after the program name, i.e. `env::args().skip(1)`.
+pub fn parse_args(args: &[String]) -> CliCommand {
+    let mut mode = None;
+    let mut path = None;
+    let mut time = false;
+
+    for arg in args {
+        if arg == "--time" {
+            time = true;
+            continue;
+        }
+
+        let flag = match arg.as_str() {
+            "--tokens" => Some(Mode::Tokens),
+            "--tokens-json" => Some(Mode::TokensJson),
+            "--ast" => Some(Mode::Ast),
+            "--check" => Some(Mode::Check),
+            arg if arg.starts_with("--") => return CliCommand::Usage,
+            _ => None,
+        };
+
+        match flag {
+            Some(_) if mode.is_some() => return CliCommand::Usage,
+            Some(_) => mode = flag,
+            None if path.is_some() => return CliCommand::Usage,
+            None => path = Some(arg.clone()),
+        }
+    }
+
+    // Only running a program file is timed.
+    if time && (mode.is_some() || path.is_none()) {
+        return CliCommand::Usage;
+    }
+
+    CliCommand::Start(CliArgs {
+        mode: mode.unwrap_or(Mode::Run),
+        path,
+        time,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> CliCommand {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(&args)
+    }
+
+    fn start(mode: Mode, path: Option<&str>) -> CliCommand {
+        CliCommand::Start(CliArgs {
+            mode,
+            path: path.map(str::to_string),
+            time: false,
+        })
+    }
+
+    #[test]
+    fn no_arguments_runs_prompt() {
+        assert_eq!(parse(&[]), start(Mode::Run, None));
+    }
+
+    #[test]
+    fn path_runs_file() {
+        assert_eq!(parse(&["script.lox"]), start(Mode::Run, Some("script.lox")));
+    }
+
+    #[test]
+    fn tokens_flag() {
+        assert_eq!(
+            parse(&["--tokens", "script.lox"]),
+            start(Mode::Tokens, Some("script.lox"))
+        );
+        assert_eq!(parse(&["--tokens"]), start(Mode::Tokens, None));
+        assert_eq!(
+            parse(&["--tokens-json", "script.lox"]),
+            start(Mode::TokensJson, Some("script.lox"))
+        );
+    }
+
+    #[test]
+    fn ast_flag() {
+        assert_eq!(
+            parse(&["script.lox", "--ast"]),
+            start(Mode::Ast, Some("script.lox"))
+        );
+    }
+
+    #[test]
+    fn check_flag() {
+        assert_eq!(
+            parse(&["--check", "script.lox"]),
+            start(Mode::Check, Some("script.lox"))
+        );
+    }
+
+    #[test]
+    fn time_flag() {
+        assert_eq!(
+            parse(&["--time", "script.lox"]),
+            CliCommand::Start(CliArgs {
+                mode: Mode::Run,
+                path: Some("script.lox".to_string()),
+                time: true,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_print_usage() {
+        assert_eq!(parse(&["a.lox", "b.lox"]), CliCommand::Usage);
+        assert_eq!(parse(&["--tokens", "--ast"]), CliCommand::Usage);
+        assert_eq!(parse(&["--check", "--ast"]), CliCommand::Usage);
+        assert_eq!(parse(&["--unknown"]), CliCommand::Usage);
+        assert_eq!(parse(&["--time", "--tokens", "a.lox"]), CliCommand::Usage);
+        assert_eq!(parse(&["--ast", "--time", "a.lox"]), CliCommand::Usage);
+        assert_eq!(parse(&["--check", "a.lox", "--time"]), CliCommand::Usage);
+        assert_eq!(parse(&["--time"]), CliCommand::Usage);
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Severity {
+    Error,
+    // Reported alongside errors but never stops the program.
+    Warning,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Diagnostic {
+    pub line: u32,
+    pub column: u32,
+    pub info: String,
+    pub msg: String,
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    // Runtime errors only know their line, marked by a zero column.
+    pub fn runtime(line: u32, msg: &str) -> Self {
+        Diagnostic {
+            line,
+            column: 0,
+            info: String::new(),
+            msg: msg.to_string(),
+            severity: Severity::Error,
+        }
+    }
+
+    pub fn warning(line: u32, column: u32, msg: &str) -> Self {
+        Diagnostic {
+            line,
+            column,
+            info: String::new(),
+            msg: msg.to_string(),
+            severity: Severity::Warning,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    // The reported source line with a caret under the column, `None` when the
+    // line is not part of `source`.
+    pub fn snippet(&self, source: &str) -> Option<String> {
+        if self.column == 0 {
+            return None;
+        }
+        let index = (self.line as usize).checked_sub(1)?;
+        let text = source.split('\n').nth(index)?.trim_end_matches('\r');
+
+        // Tabs are kept so the caret lines up however they are displayed.
+        let mut chars = text.chars();
+        let padding: String = (1..self.column)
+            .map(|_| match chars.next() {
+                Some('\t') => '\t',
+                _ => ' ',
+            })
+            .collect();
+
+        let gutter = self.line.to_string();
+        let blank = " ".repeat(gutter.len());
+        Some(format!("{gutter} | {text}\n{blank} | {padding}^"))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.column == 0 {
+            return write!(f, "{}\n[line {}]", self.msg, self.line);
+        }
+
+        write!(
+            f,
+            "[line {}, column {}] {}{}: {}",
+            self.line, self.column, self.severity, self.info, self.msg
+        )
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "Error"),
+            Severity::Warning => write!(f, "Warning"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn diagnostic(line: u32, column: u32) -> Diagnostic {
+        Diagnostic {
+            line,
+            column,
+            info: String::new(),
+            msg: "Unexpected character '@'.".to_string(),
+            severity: Severity::Error,
+        }
+    }
+
+    #[test]
+    fn snippet_points_at_column() {
+        let source = "var a = 1;\nvar b = @;\n";
+
+        assert_eq!(
+            diagnostic(2, 9).snippet(source).unwrap(),
+            "2 | var b = @;\n  |         ^"
+        );
+    }
+
+    #[test]
+    fn snippet_keeps_tabs_and_wide_gutters() {
+        let source = format!("{}\t@", "\n".repeat(9));
+
+        assert_eq!(
+            diagnostic(10, 2).snippet(&source).unwrap(),
+            "10 | \t@\n   | \t^"
+        );
+    }
+
+    #[test]
+    fn snippet_at_end_of_input() {
+        assert_eq!(
+            diagnostic(2, 1).snippet("print 1\n").unwrap(),
+            "2 | \n  | ^"
+        );
+        assert_eq!(diagnostic(3, 1).snippet("print 1\n"), None);
+    }
+
+    #[test]
+    fn runtime_diagnostic() {
+        let error = Diagnostic::runtime(3, "Operand must be a number.");
+
+        assert_eq!(error.to_string(), "Operand must be a number.\n[line 3]");
+        assert_eq!(error.snippet("\n\n-nil;"), None);
+    }
+
+    #[test]
+    fn warning_diagnostic() {
+        let warning = Diagnostic::warning(1, 11, "Local variable 'a' is never used.");
+
+        assert!(!warning.is_error());
+        assert_eq!(
+            warning.to_string(),
+            "[line 1, column 11] Warning: Local variable 'a' is never used."
+        );
+        assert_eq!(
+            warning.snippet("{ var a = 1; }").unwrap(),
+            "1 | { var a = 1; }\n  |           ^"
+        );
+    }
+}
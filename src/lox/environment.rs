@@ -0,0 +1,118 @@
+use crate::lox::interpreter::RuntimeError;
+use crate::lox::token::Token;
+use crate::lox::value::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+pub struct Environment {
+    values: HashMap<String, Value>,
+    // Names bound with `const`, which can't be assigned to.
+    constants: HashSet<String>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+            constants: HashSet::new(),
+            enclosing: None,
+        }
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            constants: HashSet::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    // Redefining a global constant with `var` makes it assignable again.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.constants.remove(name);
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn define_constant(&mut self, name: &str, value: Value) {
+        self.constants.insert(name.to_string());
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.values.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    pub fn get_own(&self, name: &str) -> Option<Value> {
+        self.values.get(name).cloned()
+    }
+
+    pub fn get_at(&self, distance: usize, name: &Token) -> Result<Value, RuntimeError> {
+        if distance == 0 {
+            return self
+                .values
+                .get(&name.lexeme)
+                .cloned()
+                .ok_or_else(|| undefined(name));
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get_at(distance - 1, name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            if self.constants.contains(&name.lexeme) {
+                return Err(constant(name));
+            }
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(undefined(name)),
+        }
+    }
+
+    pub fn assign_at(
+        &mut self,
+        distance: usize,
+        name: &Token,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        if distance == 0 {
+            if self.constants.contains(&name.lexeme) {
+                return Err(constant(name));
+            }
+            self.values.insert(name.lexeme.clone(), value);
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign_at(distance - 1, name, value),
+            None => Err(undefined(name)),
+        }
+    }
+}
+
+fn undefined(name: &Token) -> RuntimeError {
+    RuntimeError::new(name, &format!("Undefined variable '{}'.", name.lexeme))
+}
+
+fn constant(name: &Token) -> RuntimeError {
+    RuntimeError::new(
+        name,
+        &format!("Cannot assign to constant '{}'.", name.lexeme),
+    )
+}
@@ -0,0 +1,58 @@
+use crate::lox::ast::Stmt;
+use crate::lox::class::Instance;
+use crate::lox::environment::Environment;
+use crate::lox::token::Token;
+use crate::lox::value::Value;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+pub struct Function {
+    // `None` for lambdas.
+    pub name: Option<Token>,
+    pub params: Vec<Token>,
+    pub body: Rc<[Stmt]>,
+    pub closure: Rc<RefCell<Environment>>,
+    pub is_initializer: bool,
+}
+
+impl Function {
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn bind(&self, instance: Rc<RefCell<Instance>>) -> Function {
+        let mut environment = Environment::with_enclosing(self.closure.clone());
+        environment.define("this", Value::Instance(instance));
+
+        Function {
+            name: self.name.clone(),
+            params: self.params.clone(),
+            body: self.body.clone(),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+        }
+    }
+}
+
+// Functions are compared by identity, two declarations are never equal.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<fn {}>", name.lexeme),
+            None => write!(f, "<fn>"),
+        }
+    }
+}
@@ -0,0 +1,2106 @@
+use crate::lox::ast::{Expr, Stmt};
+use crate::lox::class::{Class, Instance};
+use crate::lox::environment::Environment;
+use crate::lox::function::Function;
+use crate::lox::native::{self, Console};
+use crate::lox::resolver::Locals;
+use crate::lox::token::{Token, TokenKind};
+use crate::lox::value::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::rc::Rc;
+
+#[derive(PartialEq, Debug)]
+pub struct RuntimeError {
+    pub line: u32,
+    pub msg: String,
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, msg: &str) -> Self {
+        Self {
+            line: token.line,
+            msg: msg.to_string(),
+        }
+    }
+}
+
+type RuntimeResult<T> = Result<T, RuntimeError>;
+
+#[derive(Debug, Clone)]
+pub struct InterpreterOptions {
+    // Cap on nested calls, so runaway recursion can't crash the process. The
+    // default fits a 2 MiB thread stack even in debug builds; raise it only
+    // when running on a larger stack.
+    pub max_depth: usize,
+    // Makes `/` and `%` by zero a runtime error instead of yielding infinity
+    // or NaN.
+    pub strict_division: bool,
+}
+
+impl Default for InterpreterOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 25,
+            strict_division: false,
+        }
+    }
+}
+
+// How control leaves a statement, unwound up to the enclosing call.
+#[derive(PartialEq, Debug)]
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+    // A `return` of a call to a Lox function, run by the caller's loop.
+    TailCall(Rc<Function>, Vec<Value>),
+}
+
+// `print` writes to `out`, so tests can capture program output.
+pub struct Interpreter<W>
+where
+    W: Write,
+{
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    locals: Locals,
+    out: W,
+    input: Box<dyn BufRead>,
+    // Calls in progress, capped at `options.max_depth`.
+    depth: usize,
+    options: InterpreterOptions,
+}
+
+impl<W> Interpreter<W>
+where
+    W: Write,
+{
+    pub fn output(&self) -> &W {
+        &self.out
+    }
+
+    pub fn with_output(out: W) -> Self {
+        Self::with_options(out, InterpreterOptions::default())
+    }
+
+    pub fn with_options(out: W, options: InterpreterOptions) -> Self {
+        let mut globals = Environment::new();
+        native::define_globals(&mut globals);
+
+        let globals = Rc::new(RefCell::new(globals));
+        Self {
+            globals: globals.clone(),
+            environment: globals,
+            locals: HashMap::new(),
+            out,
+            input: Box::new(BufReader::new(io::stdin())),
+            depth: 0,
+            options,
+        }
+    }
+
+    // Where `input()` reads from, stdin by default.
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = input;
+    }
+
+    pub fn set_options(&mut self, options: InterpreterOptions) {
+        self.options = options;
+    }
+
+    pub fn resolve(&mut self, locals: Locals) {
+        self.locals.extend(locals);
+    }
+
+    pub fn interpret(&mut self, statements: &[Stmt]) -> RuntimeResult<()> {
+        for stmt in statements {
+            self.execute(stmt)?;
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> RuntimeResult<Flow> {
+        match stmt {
+            Stmt::Block(statements) => {
+                let environment = Environment::with_enclosing(self.environment.clone());
+                return self.execute_block(statements, Rc::new(RefCell::new(environment)));
+            }
+            Stmt::Break(_) => return Ok(Flow::Break),
+            Stmt::Continue(_) => return Ok(Flow::Continue),
+            Stmt::Expression(expr) => {
+                self.evaluate(expr)?;
+            }
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+            } => {
+                let superclass = match superclass {
+                    Some(superclass) => match self.evaluate(superclass)? {
+                        Value::Class(class) => Some(class),
+                        _ => {
+                            let Expr::Variable(superclass_name) = superclass else {
+                                unreachable!("Superclass is always a variable.");
+                            };
+                            return Err(RuntimeError::new(
+                                superclass_name,
+                                "Superclass must be a class.",
+                            ));
+                        }
+                    },
+                    None => None,
+                };
+
+                let enclosing = self.environment.clone();
+                if let Some(superclass) = &superclass {
+                    let mut environment = Environment::with_enclosing(enclosing.clone());
+                    environment.define("super", Value::Class(superclass.clone()));
+                    self.environment = Rc::new(RefCell::new(environment));
+                }
+
+                let methods = methods
+                    .iter()
+                    .filter_map(|method| {
+                        let Stmt::Function { name, params, body } = method else {
+                            return None;
+                        };
+                        let mut function = self.function(Some(name), params, body);
+                        function.is_initializer = name.lexeme == "init";
+                        Some((name.lexeme.clone(), Rc::new(function)))
+                    })
+                    .collect();
+                self.environment = enclosing;
+
+                let class = Class {
+                    name: name.lexeme.clone(),
+                    superclass,
+                    methods,
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Value::Class(Rc::new(class)));
+            }
+            Stmt::Function { name, params, body } => {
+                let function = self.function(Some(name), params, body);
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Value::Function(Rc::new(function)));
+            }
+            Stmt::If { .. } => {
+                // Walks `else if` chains in a loop instead of recursing.
+                let mut stmt = stmt;
+                while let Stmt::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } = stmt
+                {
+                    if self.evaluate(condition)?.is_truthy() {
+                        return self.execute(then_branch);
+                    }
+                    match else_branch {
+                        Some(else_branch) => stmt = else_branch,
+                        None => return Ok(Flow::Normal),
+                    }
+                }
+                return self.execute(stmt);
+            }
+            Stmt::Print(expr) => {
+                let value = self.evaluate(expr)?;
+                let text = self.stringify(&value)?;
+                writeln!(self.out, "{text}").unwrap();
+            }
+            Stmt::Return {
+                value:
+                    Some(Expr::Call {
+                        callee,
+                        paren,
+                        arguments,
+                    }),
+                ..
+            } => {
+                let callee = self.evaluate(callee)?;
+                let arguments = self.arguments(arguments)?;
+                // Hand calls in tail position back to `call_function` so deep
+                // recursion loops instead of growing the stack.
+                if let Value::Function(function) = &callee
+                    && function.arity() == arguments.len()
+                    && !function.is_initializer
+                {
+                    return Ok(Flow::TailCall(function.clone(), arguments));
+                }
+                return Ok(Flow::Return(self.call(callee, paren, arguments)?));
+            }
+            Stmt::Return { value, .. } => {
+                let value = match value {
+                    Some(value) => self.evaluate(value)?,
+                    None => Value::Nil,
+                };
+                return Ok(Flow::Return(value));
+            }
+            Stmt::Switch {
+                value,
+                cases,
+                default,
+            } => {
+                let value = self.evaluate(value)?;
+                let mut body = default.as_deref();
+                for (case, statements) in cases {
+                    if self.evaluate(case)? == value {
+                        body = Some(statements);
+                        break;
+                    }
+                }
+
+                // Each case body is scoped like a block.
+                if let Some(body) = body {
+                    let environment = Environment::with_enclosing(self.environment.clone());
+                    return self.execute_block(body, Rc::new(RefCell::new(environment)));
+                }
+            }
+            Stmt::Const { name, initializer } => {
+                let value = self.evaluate(initializer)?;
+                self.environment
+                    .borrow_mut()
+                    .define_constant(&name.lexeme, value);
+            }
+            Stmt::Var { name, initializer } => {
+                let value = match initializer {
+                    Some(initializer) => self.evaluate(initializer)?,
+                    None => Value::Nil,
+                };
+                self.environment.borrow_mut().define(&name.lexeme, value);
+            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+            } => {
+                while self.evaluate(condition)?.is_truthy() {
+                    match self.execute(body)? {
+                        Flow::Break => break,
+                        flow @ (Flow::Return(_) | Flow::TailCall(..)) => return Ok(flow),
+                        Flow::Normal | Flow::Continue => (),
+                    }
+
+                    if let Some(increment) = increment {
+                        self.evaluate(increment)?;
+                    }
+                }
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    // Closes a function declaration over the current environment.
+    fn function(&self, name: Option<&Token>, params: &[Token], body: &Rc<[Stmt]>) -> Function {
+        Function {
+            name: name.cloned(),
+            params: params.to_vec(),
+            body: body.clone(),
+            closure: self.environment.clone(),
+            is_initializer: false,
+        }
+    }
+
+    fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Rc<RefCell<Environment>>,
+    ) -> RuntimeResult<Flow> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = self.execute_all(statements);
+        self.environment = previous;
+
+        result
+    }
+
+    fn execute_all(&mut self, statements: &[Stmt]) -> RuntimeResult<Flow> {
+        for stmt in statements {
+            let flow = self.execute(stmt)?;
+            if flow != Flow::Normal {
+                return Ok(flow);
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> RuntimeResult<Value> {
+        match expr {
+            Expr::ArrayLiteral { elements, .. } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.evaluate(element))
+                    .collect::<RuntimeResult<Vec<_>>>()?;
+                Ok(Value::List(Rc::new(RefCell::new(elements))))
+            }
+            Expr::Assign { name, value } => {
+                let value = self.evaluate(value)?;
+                self.assign_variable(name, expr, value.clone())?;
+                Ok(value)
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                self.binary(operator, left, right)
+            }
+            Expr::Call {
+                callee,
+                paren,
+                arguments,
+            } => {
+                let callee = self.evaluate(callee)?;
+                let arguments = self.arguments(arguments)?;
+                self.call(callee, paren, arguments)
+            }
+            Expr::Get { object, name } => match self.evaluate(object)? {
+                Value::Instance(instance) => Instance::get(&instance, name),
+                _ => Err(RuntimeError::new(name, "Only instances have properties.")),
+            },
+            Expr::Grouping(expr) => self.evaluate(expr),
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => {
+                let Value::List(elements) = self.evaluate(object)? else {
+                    return Err(RuntimeError::new(bracket, "Only lists can be indexed."));
+                };
+                let index = self.evaluate(index)?;
+                let elements = elements.borrow();
+                let index = list_index(bracket, index, elements.len())?;
+                Ok(elements[index].clone())
+            }
+            Expr::IndexSet {
+                object,
+                bracket,
+                index,
+                value,
+            } => {
+                let Value::List(elements) = self.evaluate(object)? else {
+                    return Err(RuntimeError::new(bracket, "Only lists can be indexed."));
+                };
+                let index = self.evaluate(index)?;
+                let value = self.evaluate(value)?;
+                let mut elements = elements.borrow_mut();
+                let index = list_index(bracket, index, elements.len())?;
+                elements[index] = value.clone();
+                Ok(value)
+            }
+            Expr::Lambda { params, body, .. } => {
+                Ok(Value::Function(Rc::new(self.function(None, params, body))))
+            }
+            Expr::Literal(literal) => Ok(Value::from(literal)),
+            Expr::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.evaluate(left)?;
+                let short_circuit = match operator.kind {
+                    TokenKind::Or => left.is_truthy(),
+                    TokenKind::And => !left.is_truthy(),
+                    _ => unreachable!("Invalid logical operator {:?}.", operator.kind),
+                };
+
+                if short_circuit {
+                    Ok(left)
+                } else {
+                    self.evaluate(right)
+                }
+            }
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => {
+                let Value::Instance(instance) = self.evaluate(object)? else {
+                    return Err(RuntimeError::new(name, "Only instances have fields."));
+                };
+
+                let value = self.evaluate(value)?;
+                instance.borrow_mut().set(name, value.clone());
+                Ok(value)
+            }
+            Expr::Increment {
+                name,
+                operator,
+                prefix,
+            } => {
+                let Value::Number(old) = self.look_up_variable(name, expr)? else {
+                    return Err(RuntimeError::new(operator, "Operand must be a number."));
+                };
+                let new = match operator.kind {
+                    TokenKind::PlusPlus => old + 1.0,
+                    _ => old - 1.0,
+                };
+                self.assign_variable(name, expr, Value::Number(new))?;
+                Ok(Value::Number(if *prefix { new } else { old }))
+            }
+            Expr::Super { keyword, method } => self.super_method(expr, keyword, method),
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    self.evaluate(then_branch)
+                } else {
+                    self.evaluate(else_branch)
+                }
+            }
+            Expr::This(keyword) => self.look_up_variable(keyword, expr),
+            Expr::Unary { operator, right } => {
+                let right = self.evaluate(right)?;
+                self.unary(operator, right)
+            }
+            Expr::Variable(name) => self.look_up_variable(name, expr),
+        }
+    }
+
+    // `this` lives one scope inside the scope binding `super`.
+    fn super_method(&self, expr: &Expr, keyword: &Token, method: &Token) -> RuntimeResult<Value> {
+        let distance = self
+            .local_depth(expr)
+            .expect("Resolver binds 'super' inside subclasses.");
+        let environment = self.environment.borrow();
+
+        let Value::Class(superclass) = environment.get_at(distance, keyword)? else {
+            unreachable!("'super' is always bound to a class.");
+        };
+        let this = Token {
+            kind: TokenKind::This,
+            lexeme: "this".to_string(),
+            literal: None,
+            ..keyword.clone()
+        };
+        let Value::Instance(instance) = environment.get_at(distance - 1, &this)? else {
+            unreachable!("'this' is always bound to an instance.");
+        };
+
+        match superclass.find_method(&method.lexeme) {
+            Some(function) => Ok(Value::Function(Rc::new(function.bind(instance)))),
+            None => Err(RuntimeError::new(
+                method,
+                &format!("Undefined property '{}'.", method.lexeme),
+            )),
+        }
+    }
+
+    fn look_up_variable(&self, name: &Token, expr: &Expr) -> RuntimeResult<Value> {
+        match self.local_depth(expr) {
+            Some(distance) => self.environment.borrow().get_at(distance, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    fn assign_variable(&self, name: &Token, expr: &Expr, value: Value) -> RuntimeResult<()> {
+        match self.local_depth(expr) {
+            Some(distance) => self
+                .environment
+                .borrow_mut()
+                .assign_at(distance, name, value),
+            None => self.globals.borrow_mut().assign(name, value),
+        }
+    }
+
+    fn local_depth(&self, expr: &Expr) -> Option<usize> {
+        self.locals.get(&(expr as *const Expr)).copied().flatten()
+    }
+
+    fn arguments(&mut self, arguments: &[Expr]) -> RuntimeResult<Vec<Value>> {
+        arguments
+            .iter()
+            .map(|argument| self.evaluate(argument))
+            .collect()
+    }
+
+    fn call(
+        &mut self,
+        callee: Value,
+        paren: &Token,
+        arguments: Vec<Value>,
+    ) -> RuntimeResult<Value> {
+        let arity = match &callee {
+            Value::Function(function) => function.arity()..=function.arity(),
+            Value::Native { arity, .. } => arity.clone(),
+            Value::Class(class) => class.arity()..=class.arity(),
+            _ => {
+                return Err(RuntimeError::new(
+                    paren,
+                    "Can only call functions and classes.",
+                ));
+            }
+        };
+
+        if !arity.contains(&arguments.len()) {
+            let expected = if arity.start() == arity.end() {
+                arity.start().to_string()
+            } else {
+                format!("{} to {}", arity.start(), arity.end())
+            };
+            return Err(RuntimeError::new(
+                paren,
+                &format!(
+                    "Expected {} arguments but got {}.",
+                    expected,
+                    arguments.len()
+                ),
+            ));
+        }
+
+        if self.depth == self.options.max_depth {
+            return Err(RuntimeError::new(paren, "Stack overflow."));
+        }
+
+        self.depth += 1;
+        let result = match callee {
+            Value::Native { func, .. } => {
+                func(self, &arguments).map_err(|msg| RuntimeError::new(paren, &msg))
+            }
+            Value::Function(function) => self.call_function(&function, arguments),
+            Value::Class(class) => {
+                let instance = Rc::new(RefCell::new(Instance::new(class.clone())));
+                match class.find_method("init") {
+                    // Initializers return the instance they are bound to.
+                    Some(init) => self.call_function(&init.bind(instance), arguments),
+                    None => Ok(Value::Instance(instance)),
+                }
+            }
+            _ => unreachable!("Callee {callee:?} is not callable."),
+        };
+        self.depth -= 1;
+
+        result
+    }
+
+    // Instances whose class has a zero-argument `toString` method are shown
+    // as whatever it returns, including inside lists.
+    fn stringify(&mut self, value: &Value) -> RuntimeResult<String> {
+        match value {
+            Value::Instance(instance) => {
+                let method = instance.borrow().class.find_method("toString");
+                if let Some(method) = method.filter(|method| method.arity() == 0) {
+                    // Goes through `call` so runaway `toString` recursion hits the
+                    // depth limit. Errors point at the method's declaration.
+                    let name = method.name.clone().expect("Methods are named.");
+                    let method = Value::Function(Rc::new(method.bind(instance.clone())));
+                    return Ok(self.call(method, &name, vec![])?.to_string());
+                }
+                Ok(value.to_string())
+            }
+            Value::List(elements) => {
+                // A `toString` may change the list, so walk a snapshot of it.
+                let elements = elements.borrow().clone();
+                let elements = elements
+                    .iter()
+                    .map(|element| self.stringify(element))
+                    .collect::<RuntimeResult<Vec<_>>>()?;
+                Ok(format!("[{}]", elements.join(", ")))
+            }
+            _ => Ok(value.to_string()),
+        }
+    }
+
+    fn call_function(
+        &mut self,
+        function: &Function,
+        arguments: Vec<Value>,
+    ) -> RuntimeResult<Value> {
+        let mut flow = self.execute_body(function, arguments)?;
+        while let Flow::TailCall(function, arguments) = flow {
+            flow = self.execute_body(&function, arguments)?;
+        }
+
+        // Initializers always hand back the instance, even on an early return.
+        if function.is_initializer {
+            let this = function.closure.borrow().get_own("this");
+            return Ok(this.expect("Initializer is bound to an instance."));
+        }
+
+        match flow {
+            Flow::Return(value) => Ok(value),
+            // The parser keeps `break` and `continue` inside loops.
+            Flow::Normal | Flow::Break | Flow::Continue => Ok(Value::Nil),
+            Flow::TailCall(..) => unreachable!("Tail calls are run before returning."),
+        }
+    }
+
+    fn execute_body(&mut self, function: &Function, arguments: Vec<Value>) -> RuntimeResult<Flow> {
+        let mut environment = Environment::with_enclosing(function.closure.clone());
+        for (param, argument) in function.params.iter().zip(arguments) {
+            environment.define(&param.lexeme, argument);
+        }
+        self.execute_block(&function.body, Rc::new(RefCell::new(environment)))
+    }
+
+    fn unary(&self, operator: &Token, right: Value) -> RuntimeResult<Value> {
+        match operator.kind {
+            TokenKind::Minus => match right {
+                Value::Number(value) => Ok(Value::Number(-value)),
+                _ => Err(RuntimeError::new(operator, "Operand must be a number.")),
+            },
+            TokenKind::Bang => Ok(Value::Bool(!right.is_truthy())),
+            TokenKind::Tilde => match right {
+                Value::Number(value) => Ok(Value::Number(!integer(operator, value)? as f64)),
+                _ => Err(RuntimeError::new(operator, "Operand must be a number.")),
+            },
+            _ => unreachable!("Invalid unary operator {:?}.", operator.kind),
+        }
+    }
+
+    fn binary(&self, operator: &Token, left: Value, right: Value) -> RuntimeResult<Value> {
+        match operator.kind {
+            TokenKind::EqualEqual => return Ok(Value::Bool(left == right)),
+            TokenKind::BangEqual => return Ok(Value::Bool(left != right)),
+            TokenKind::Plus => return self.plus(operator, left, right),
+            _ => (),
+        }
+
+        let (Value::Number(left), Value::Number(right)) = (left, right) else {
+            return Err(RuntimeError::new(operator, "Operands must be numbers."));
+        };
+
+        if self.options.strict_division
+            && matches!(operator.kind, TokenKind::Slash | TokenKind::Percent)
+            && right == 0.0
+        {
+            return Err(RuntimeError::new(operator, "Division by zero."));
+        }
+
+        let value = match operator.kind {
+            TokenKind::Minus => Value::Number(left - right),
+            TokenKind::Star => Value::Number(left * right),
+            TokenKind::Slash => Value::Number(left / right),
+            TokenKind::Percent => Value::Number(left % right),
+            TokenKind::Greater => Value::Bool(left > right),
+            TokenKind::GreaterEqual => Value::Bool(left >= right),
+            TokenKind::Less => Value::Bool(left < right),
+            TokenKind::LessEqual => Value::Bool(left <= right),
+            TokenKind::Ampersand | TokenKind::Pipe | TokenKind::Caret => {
+                let (left, right) = (integer(operator, left)?, integer(operator, right)?);
+                let value = match operator.kind {
+                    TokenKind::Ampersand => left & right,
+                    TokenKind::Pipe => left | right,
+                    _ => left ^ right,
+                };
+                Value::Number(value as f64)
+            }
+            TokenKind::LessLess | TokenKind::GreaterGreater => {
+                let (left, right) = (integer(operator, left)?, integer(operator, right)?);
+                let Ok(shift @ 0..64) = u32::try_from(right) else {
+                    return Err(RuntimeError::new(
+                        operator,
+                        "Shift amount must be between 0 and 63.",
+                    ));
+                };
+                let value = match operator.kind {
+                    TokenKind::LessLess => left << shift,
+                    _ => left >> shift,
+                };
+                Value::Number(value as f64)
+            }
+            _ => unreachable!("Invalid binary operator {:?}.", operator.kind),
+        };
+
+        Ok(value)
+    }
+
+    fn plus(&self, operator: &Token, left: Value, right: Value) -> RuntimeResult<Value> {
+        match (left, right) {
+            (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left + right)),
+            (Value::Str(left), Value::Str(right)) => Ok(Value::Str(left + &right)),
+            _ => Err(RuntimeError::new(
+                operator,
+                "Operands must be two numbers or two strings.",
+            )),
+        }
+    }
+}
+
+impl<W> Console for Interpreter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        write!(self.out, "{text}")?;
+        self.out.flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        match self.input.read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+
+    fn stringify(&mut self, value: &Value) -> Result<String, String> {
+        Interpreter::stringify(self, value).map_err(|error| error.msg)
+    }
+}
+
+// A whole number within the bounds of a list of `len` elements.
+fn list_index(bracket: &Token, index: Value, len: usize) -> RuntimeResult<usize> {
+    let Value::Number(index) = index else {
+        return Err(RuntimeError::new(bracket, "List index must be a number."));
+    };
+    if index.fract() != 0.0 {
+        return Err(RuntimeError::new(bracket, "List index must be an integer."));
+    }
+    if index < 0.0 || index >= len as f64 {
+        return Err(RuntimeError::new(bracket, "List index out of bounds."));
+    }
+
+    Ok(index as usize)
+}
+
+// Bitwise operators work on whole numbers only.
+fn integer(operator: &Token, value: f64) -> RuntimeResult<i64> {
+    if value.fract() != 0.0 || !value.is_finite() {
+        return Err(RuntimeError::new(operator, "Operands must be integers."));
+    }
+
+    Ok(value as i64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lox::parser::Parser;
+    use crate::lox::resolver::Resolver;
+    use crate::lox::scanner::Scanner;
+    use crate::lox::{Lox, MAX_DEPTH, STACK_SIZE};
+    use std::io::Cursor;
+    use std::thread;
+
+    fn run(code: &str) -> (Interpreter<Vec<u8>>, RuntimeResult<()>) {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, code).scan();
+        let statements = Parser::new(&mut lox, tokens).parse();
+        let mut interpreter = Interpreter::with_output(vec![]);
+        interpreter.resolve(Resolver::new(&mut lox).resolve(&statements));
+        assert!(!lox.has_error, "compile errors: {:?}", lox.errors);
+        let result = interpreter.interpret(&statements);
+        (interpreter, result)
+    }
+
+    fn global(interpreter: &Interpreter<Vec<u8>>, name: &str) -> RuntimeResult<Value> {
+        let token = Token {
+            kind: TokenKind::Identifier,
+            line: 1,
+            column: 1,
+            start: 0,
+            end: name.len(),
+            lexeme: name.to_string(),
+            literal: None,
+        };
+        interpreter.environment.borrow().get(&token)
+    }
+
+    fn evaluate(code: &str) -> RuntimeResult<Value> {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, &format!("{code};")).scan();
+        let statements = Parser::new(&mut lox, tokens).parse();
+        let [Stmt::Expression(expr)] = statements.as_slice() else {
+            panic!("Expected a single expression statement.");
+        };
+        Interpreter::with_output(vec![]).evaluate(expr)
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(Value::Number(7.0)));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(Value::Number(9.0)));
+        assert_eq!(evaluate("10 / 4 - -1"), Ok(Value::Number(3.5)));
+    }
+
+    #[test]
+    fn comparison_and_equality() {
+        assert_eq!(evaluate("1 < 2"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("2 <= 1"), Ok(Value::Bool(false)));
+        assert_eq!(evaluate("3 >= 3 == true"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("nil == nil"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("\"a\" != \"b\""), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("1 == \"1\""), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn truthiness() {
+        assert_eq!(evaluate("!nil"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("!false"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("!0"), Ok(Value::Bool(false)));
+        assert_eq!(evaluate("!\"\""), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn mismatched_operands() {
+        assert_eq!(
+            evaluate("1 * \"a\""),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Operands must be numbers.".to_string(),
+            })
+        );
+        assert_eq!(
+            evaluate("-\"a\""),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Operand must be a number.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn plus() {
+        assert_eq!(
+            evaluate("\"foo\" + \"bar\""),
+            Ok(Value::Str("foobar".to_string()))
+        );
+        assert_eq!(evaluate("1 + 2"), Ok(Value::Number(3.0)));
+        assert_eq!(
+            evaluate("1 + \"x\""),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Operands must be two numbers or two strings.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn variables() {
+        let (interpreter, result) = run("var a = 1; var b = a + 2; var c;");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(1.0)));
+        assert_eq!(global(&interpreter, "b"), Ok(Value::Number(3.0)));
+        assert_eq!(global(&interpreter, "c"), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn undefined_variable() {
+        let (_, result) = run("var a = 1;\nprint a + x;");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 2,
+                msg: "Undefined variable 'x'.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn assignment() {
+        let (interpreter, result) = run("var a = 1; a = 2; print a; var b = a = 3;");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(3.0)));
+        assert_eq!(global(&interpreter, "b"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn assign_undefined_variable() {
+        let (_, result) = run("x = 1;");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 1,
+                msg: "Undefined variable 'x'.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn constants() {
+        let (interpreter, result) = run("const a = 1; var b = a + 1;");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "b"), Ok(Value::Number(2.0)));
+
+        for code in [
+            "const x = 1; x = 2;",
+            "{ const x = 1; x += 1; }",
+            "const x = 1; x++;",
+        ] {
+            assert_eq!(
+                run(code).1,
+                Err(RuntimeError {
+                    line: 1,
+                    msg: "Cannot assign to constant 'x'.".to_string(),
+                }),
+                "{code}"
+            );
+        }
+
+        let (interpreter, result) = run("const a = 1; var a = 2; a = 3;");
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn block_scopes() {
+        let (interpreter, result) = run("var a = \"outer\";
+            var b = \"outer\";
+            var inner;
+            {
+                var a = \"inner\";
+                b = a;
+                inner = a;
+            }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "a"),
+            Ok(Value::Str("outer".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "b"),
+            Ok(Value::Str("inner".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "inner"),
+            Ok(Value::Str("inner".to_string()))
+        );
+    }
+
+    #[test]
+    fn block_locals_do_not_leak() {
+        let (interpreter, result) = run("{ var local = 1; }\nprint local;");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 2,
+                msg: "Undefined variable 'local'.".to_string(),
+            })
+        );
+        assert!(global(&interpreter, "local").is_err());
+    }
+
+    #[test]
+    fn error_in_block_restores_scope() {
+        let (interpreter, result) = run("var a = 1; { var a = 2; a + nil; }");
+
+        assert!(result.is_err());
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn if_statements() {
+        let (interpreter, result) = run("var a; var b; var c = \"untouched\";
+            if (1 < 2) a = \"then\";
+            if (nil) b = \"then\"; else b = \"else\";
+            if (false) c = \"then\";");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "a"),
+            Ok(Value::Str("then".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "b"),
+            Ok(Value::Str("else".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "c"),
+            Ok(Value::Str("untouched".to_string()))
+        );
+    }
+
+    #[test]
+    fn dangling_else_binds_to_inner_if() {
+        let (interpreter, result) = run("var a = \"none\";
+            if (true) if (false) a = \"inner then\"; else a = \"inner else\";");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "a"),
+            Ok(Value::Str("inner else".to_string()))
+        );
+    }
+
+    #[test]
+    fn while_loop() {
+        let (interpreter, result) = run("var i = 0; while (i < 3) i = i + 1; print i;");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "i"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn while_loop_with_block_body() {
+        let (interpreter, result) = run("var i = 0; var total = 0;
+            while (i < 4) {
+                var square = i * i;
+                total = total + square;
+                i = i + 1;
+            }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "total"), Ok(Value::Number(14.0)));
+        assert!(global(&interpreter, "square").is_err());
+    }
+
+    #[test]
+    fn for_loop_matches_while_loop() {
+        let (for_loop, for_result) = run("var total = 0;
+            for (var i = 0; i < 5; i = i + 1) total = total + i;");
+        let (while_loop, while_result) = run("var total = 0;
+            { var i = 0; while (i < 5) { total = total + i; i = i + 1; } }");
+
+        assert_eq!(for_result, Ok(()));
+        assert_eq!(while_result, Ok(()));
+        assert_eq!(global(&for_loop, "total"), Ok(Value::Number(10.0)));
+        assert_eq!(global(&for_loop, "total"), global(&while_loop, "total"));
+        assert!(global(&for_loop, "i").is_err());
+    }
+
+    #[test]
+    fn for_loop_with_outer_counter() {
+        let (interpreter, result) = run("var i = 0; var steps = 0;
+            for (; i < 3;) { i = i + 1; steps = steps + 1; }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "i"), Ok(Value::Number(3.0)));
+        assert_eq!(global(&interpreter, "steps"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn logical_operators_return_operands() {
+        assert_eq!(evaluate("nil or 2"), Ok(Value::Number(2.0)));
+        assert_eq!(evaluate("1 or 2"), Ok(Value::Number(1.0)));
+        assert_eq!(evaluate("1 and 2"), Ok(Value::Number(2.0)));
+        assert_eq!(evaluate("false and 2"), Ok(Value::Bool(false)));
+        assert_eq!(
+            evaluate("nil and 1 or \"default\""),
+            Ok(Value::Str("default".to_string()))
+        );
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        let (interpreter, result) = run("var a = \"untouched\"; var b = \"untouched\";
+            var x = true or (a = \"evaluated\");
+            var y = nil and (b = \"evaluated\");");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "x"), Ok(Value::Bool(true)));
+        assert_eq!(global(&interpreter, "y"), Ok(Value::Nil));
+        assert_eq!(
+            global(&interpreter, "a"),
+            Ok(Value::Str("untouched".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "b"),
+            Ok(Value::Str("untouched".to_string()))
+        );
+    }
+
+    #[test]
+    fn call_function() {
+        let (interpreter, result) = run("var sum;
+            fun add(a, b) { sum = a + b; }
+            add(1, 2);");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "sum"), Ok(Value::Number(3.0)));
+        assert!(global(&interpreter, "a").is_err());
+    }
+
+    #[test]
+    fn function_closes_over_declaring_scope() {
+        let (interpreter, result) = run("var seen;
+            {
+                var secret = \"inner\";
+                fun reveal() { seen = secret; }
+                reveal();
+            }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "seen"),
+            Ok(Value::Str("inner".to_string()))
+        );
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        let (_, result) = run("fun add(a, b) {}\nadd(1);");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 2,
+                msg: "Expected 2 arguments but got 1.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn call_non_callable() {
+        assert_eq!(
+            evaluate("\"not a function\"()"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Can only call functions and classes.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn mixed_call_chain() {
+        let (interpreter, result) = run("class Box { init(items) { this.items = items; } \
+               get() { return this.items; } } \
+             fun add(a) { return fun (b) { return Box([a, a + b]); }; } \
+             var result = add(1)(2).get()[1];");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "result"), Ok(Value::Number(3.0)));
+        assert_eq!(
+            run("fun f() { return 1; } f()();").1,
+            Err(RuntimeError {
+                line: 1,
+                msg: "Can only call functions and classes.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn return_exits_loop_early() {
+        let (interpreter, result) = run("var iterations = 0;
+            fun find(limit) {
+                var i = 0;
+                while (true) {
+                    iterations = iterations + 1;
+                    { if (i * i > limit) return i; }
+                    i = i + 1;
+                }
+            }
+            var found = find(10);");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "found"), Ok(Value::Number(4.0)));
+        assert_eq!(global(&interpreter, "iterations"), Ok(Value::Number(5.0)));
+    }
+
+    #[test]
+    fn function_without_return_yields_nil() {
+        let (interpreter, result) = run("fun f() { 1; } var a = f();");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn native_clock() {
+        let first = evaluate("clock()");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let second = evaluate("clock()");
+
+        let (Ok(Value::Number(first)), Ok(Value::Number(second))) = (first, second) else {
+            panic!("Expected clock() to return numbers.");
+        };
+        assert!(second >= first);
+        assert_eq!(
+            evaluate("clock(1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Expected 0 arguments but got 1.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn closure_captures_binding_at_declaration() {
+        let (interpreter, result) = run("var a = \"global\";
+            var first; var second;
+            {
+                fun show() { return a; }
+                first = show();
+                var a = \"block\";
+                second = show();
+            }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "first"),
+            Ok(Value::Str("global".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "second"),
+            Ok(Value::Str("global".to_string()))
+        );
+    }
+
+    #[test]
+    fn class_fields_and_methods() {
+        let (interpreter, result) = run("class Point {
+                describe() { return \"a point\"; }
+            }
+            var point = Point();
+            point.x = 1;
+            point.x = point.x + 1;
+            var x = point.x;
+            var description = point.describe();");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "x"), Ok(Value::Number(2.0)));
+        assert_eq!(
+            global(&interpreter, "description"),
+            Ok(Value::Str("a point".to_string()))
+        );
+    }
+
+    #[test]
+    fn undefined_property() {
+        let (_, result) = run("class A {}\nA().missing;");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 2,
+                msg: "Undefined property 'missing'.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn properties_on_non_instances() {
+        assert_eq!(
+            evaluate("1 .field"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Only instances have properties.".to_string(),
+            })
+        );
+        assert_eq!(
+            evaluate("\"str\".field = 1"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Only instances have fields.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn methods_bind_this() {
+        let (interpreter, result) = run("class Box {
+                get() { return this.field; }
+                set(value) { this.field = value; }
+            }
+            var box = Box();
+            box.set(\"stored\");
+            var detached = box.get;
+            var value = detached();");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "value"),
+            Ok(Value::Str("stored".to_string()))
+        );
+    }
+
+    #[test]
+    fn init_constructor() {
+        let (interpreter, result) = run("class Pair {
+                init(a, b) {
+                    this.first = a;
+                    this.second = b;
+                    if (b == nil) return;
+                    this.complete = true;
+                }
+            }
+            var pair = Pair(1, 2);
+            var sum = pair.first + pair.second;
+            var reinit = pair.init(3, nil);
+            var same = reinit == pair;
+            var first = pair.first;");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "sum"), Ok(Value::Number(3.0)));
+        assert_eq!(global(&interpreter, "same"), Ok(Value::Bool(true)));
+        assert_eq!(global(&interpreter, "first"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn init_arity_mismatch() {
+        let (_, result) = run("class Pair { init(a, b) {} }\nPair(1);");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 2,
+                msg: "Expected 2 arguments but got 1.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn inherited_methods() {
+        let (interpreter, result) = run("class Animal {
+                init(name) { this.name = name; }
+                speak() { return this.name + \" makes a sound\"; }
+            }
+            class Dog < Animal {}
+            class Puppy < Dog {}
+            var sound = Puppy(\"Rex\").speak();");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "sound"),
+            Ok(Value::Str("Rex makes a sound".to_string()))
+        );
+    }
+
+    #[test]
+    fn superclass_must_be_a_class() {
+        let (_, result) = run("var NotAClass = 1;\nclass A < NotAClass {}");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 2,
+                msg: "Superclass must be a class.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn super_calls_overridden_method() {
+        let (interpreter, result) = run("class Base {
+                describe() { return \"base of \" + this.name; }
+            }
+            class Derived < Base {
+                init(name) { this.name = name; }
+                describe() { return \"derived, \" + super.describe(); }
+            }
+            class Leaf < Derived {}
+            var description = Leaf(\"leaf\").describe();");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "description"),
+            Ok(Value::Str("derived, base of leaf".to_string()))
+        );
+    }
+
+    #[test]
+    fn super_undefined_method() {
+        let (_, result) = run("class A {}
+            class B < A { m() { return super.missing; } }
+            B().m();");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 2,
+                msg: "Undefined property 'missing'.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn modulo() {
+        assert_eq!(evaluate("7 % 3"), Ok(Value::Number(1.0)));
+        assert_eq!(evaluate("-7 % 3"), Ok(Value::Number(-1.0)));
+        assert_eq!(evaluate("7.5 % 2"), Ok(Value::Number(1.5)));
+        assert!(matches!(evaluate("1 % 0"), Ok(Value::Number(value)) if value.is_nan()));
+        assert_eq!(
+            evaluate("7 % \"3\""),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Operands must be numbers.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn ternary() {
+        assert_eq!(evaluate("true ? 1 : 2"), Ok(Value::Number(1.0)));
+        assert_eq!(evaluate("nil ? 1 : 2"), Ok(Value::Number(2.0)));
+        assert_eq!(
+            evaluate("false ? \"a\" : 0 ? \"b\" : \"c\""),
+            Ok(Value::Str("b".to_string()))
+        );
+    }
+
+    #[test]
+    fn ternary_evaluates_one_branch() {
+        let (interpreter, result) = run("var a = \"untouched\";
+            var b = true ? \"then\" : (a = \"else\");");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "a"),
+            Ok(Value::Str("untouched".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "b"),
+            Ok(Value::Str("then".to_string()))
+        );
+    }
+
+    #[test]
+    fn break_exits_loop() {
+        let (interpreter, result) = run("var i = 0;
+            while (true) {
+                if (i == 3) break;
+                i = i + 1;
+            }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "i"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn continue_runs_for_increment() {
+        let (interpreter, result) = run("var odd = 0; var iterations = 0;
+            for (var i = 0; i < 6; i = i + 1) {
+                iterations = iterations + 1;
+                if (i % 2 == 0) continue;
+                odd = odd + i;
+                for (;;) break;
+            }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "odd"), Ok(Value::Number(9.0)));
+        assert_eq!(global(&interpreter, "iterations"), Ok(Value::Number(6.0)));
+    }
+
+    #[test]
+    fn lambda_passed_to_higher_order_function() {
+        let (interpreter, result) = run("fun twice(f, x) { return f(f(x)); }
+            var offset = 3;
+            var result = twice(fun (n) { return n + offset; }, 1);
+            var anonymous = fun () {};");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "result"), Ok(Value::Number(7.0)));
+        assert_eq!(
+            global(&interpreter, "anonymous").map(|value| value.to_string()),
+            Ok("<fn>".to_string())
+        );
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let (interpreter, result) =
+            run("var x = 1; x += 4; var y = 10; { y -= 2; y *= 3; y /= 4; }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "x"), Ok(Value::Number(5.0)));
+        assert_eq!(global(&interpreter, "y"), Ok(Value::Number(6.0)));
+    }
+
+    #[test]
+    fn compound_property_assignment() {
+        let (interpreter, result) =
+            run("class A {} var a = A(); a.s = \"x\"; a.s += \"y\"; var s = a.s;");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "s"), Ok(Value::Str("xy".to_string())));
+    }
+
+    #[test]
+    fn prefix_and_postfix_increment() {
+        let (interpreter, result) = run(
+            "var a = 1; var b = a++; var c = 1; var d = ++c; var e; { var f = 5; f--; e = --f; }",
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(2.0)));
+        assert_eq!(global(&interpreter, "b"), Ok(Value::Number(1.0)));
+        assert_eq!(global(&interpreter, "c"), Ok(Value::Number(2.0)));
+        assert_eq!(global(&interpreter, "d"), Ok(Value::Number(2.0)));
+        assert_eq!(global(&interpreter, "e"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn increment_non_number() {
+        let (_, result) = run("var a = \"a\"; a++;");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 1,
+                msg: "Operand must be a number.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn print_to_output() {
+        let (interpreter, result) = run("print 1 + 2; print \"a\"; print nil;");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(String::from_utf8(interpreter.out).unwrap(), "3\na\nnil\n");
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert_eq!(evaluate("6 & 3"), Ok(Value::Number(2.0)));
+        assert_eq!(evaluate("6 | 3"), Ok(Value::Number(7.0)));
+        assert_eq!(evaluate("6 ^ 3"), Ok(Value::Number(5.0)));
+        assert_eq!(evaluate("~5"), Ok(Value::Number(-6.0)));
+        assert_eq!(evaluate("1 << 4"), Ok(Value::Number(16.0)));
+        assert_eq!(evaluate("-16 >> 2"), Ok(Value::Number(-4.0)));
+        assert_eq!(evaluate("1 | 2 == 3"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("1 << 2 < 5"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn bitwise_operands_must_be_integers() {
+        assert_eq!(
+            evaluate("1.5 & 1"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Operands must be integers.".to_string(),
+            })
+        );
+        assert_eq!(
+            evaluate("1 << 64"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Shift amount must be between 0 and 63.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn equality_semantics() {
+        assert_eq!(evaluate("nil == false"), Ok(Value::Bool(false)));
+        assert_eq!(evaluate("nil != 0"), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("\"a\" == \"a\""), Ok(Value::Bool(true)));
+        assert_eq!(evaluate("(0/0) == (0/0)"), Ok(Value::Bool(false)));
+        assert_eq!(evaluate("clock == clock"), Ok(Value::Bool(true)));
+        assert_eq!(
+            evaluate("(fun () {}) == (fun () {})"),
+            Ok(Value::Bool(false))
+        );
+    }
+
+    #[test]
+    fn list_literals_and_indexing() {
+        let (interpreter, result) = run(
+            "var empty = []; var list = [1, \"two\", [3],]; var a = list[0]; var b = list[2][0]; print list;",
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(1.0)));
+        assert_eq!(global(&interpreter, "b"), Ok(Value::Number(3.0)));
+        assert_eq!(String::from_utf8_lossy(&interpreter.out), "[1, two, [3]]\n");
+        let Ok(Value::List(empty)) = global(&interpreter, "empty") else {
+            panic!("Expected a list.");
+        };
+        assert!(empty.borrow().is_empty());
+    }
+
+    #[test]
+    fn invalid_list_indexing() {
+        let cases = [
+            ("[1, 2][2]", "List index out of bounds."),
+            ("[1, 2][-1]", "List index out of bounds."),
+            ("[1, 2][0.5]", "List index must be an integer."),
+            ("[1, 2][\"0\"]", "List index must be a number."),
+            ("\"ab\"[0]", "Only lists can be indexed."),
+        ];
+
+        for (code, msg) in cases {
+            assert_eq!(
+                evaluate(code),
+                Err(RuntimeError {
+                    line: 1,
+                    msg: msg.to_string(),
+                }),
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn list_assignment_through_alias() {
+        let (interpreter, result) = run(
+            "var a = [1, 2, 3]; var b = a; b[1] = \"x\"; a[2] += 10; var c = a[1]; var d = b[2];",
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "c"), Ok(Value::Str("x".to_string())));
+        assert_eq!(global(&interpreter, "d"), Ok(Value::Number(13.0)));
+    }
+
+    #[test]
+    fn list_assignment_out_of_bounds() {
+        for code in ["var a = [1]; a[1] = 2;", "var a = [1]; a[-1] = 2;"] {
+            let (_, result) = run(code);
+
+            assert_eq!(
+                result,
+                Err(RuntimeError {
+                    line: 1,
+                    msg: "List index out of bounds.".to_string(),
+                }),
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn native_len() {
+        assert_eq!(evaluate("len(\"héllo\")"), Ok(Value::Number(5.0)));
+        assert_eq!(evaluate("len([1, 2, 3])"), Ok(Value::Number(3.0)));
+        assert_eq!(evaluate("len([])"), Ok(Value::Number(0.0)));
+        assert_eq!(
+            evaluate("len(1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "len() expects a string or list.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn native_string_functions() {
+        assert_eq!(
+            evaluate("upper(\"héllo\")"),
+            Ok(Value::Str("HÉLLO".to_string()))
+        );
+        assert_eq!(
+            evaluate("lower(\"LoX\")"),
+            Ok(Value::Str("lox".to_string()))
+        );
+        assert_eq!(
+            evaluate("trim(\" \\t a b \\n\")"),
+            Ok(Value::Str("a b".to_string()))
+        );
+        assert_eq!(
+            evaluate("upper(1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "upper() expects a string.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn native_split() {
+        let split = |code| evaluate(code).map(|value| value.to_string());
+
+        assert_eq!(
+            split("split(\"a,b,c\", \",\")"),
+            Ok("[a, b, c]".to_string())
+        );
+        assert_eq!(split("len(split(\"a,b,c\", \",\"))"), Ok("3".to_string()));
+        assert_eq!(split("split(\"abc\", \"\")"), Ok("[a, b, c]".to_string()));
+        assert_eq!(split("split(\"abc\", \";\")"), Ok("[abc]".to_string()));
+        assert_eq!(split("split(\"a, b\", \", \")"), Ok("[a, b]".to_string()));
+        assert_eq!(split("split(\"\", \"\")"), Ok("[]".to_string()));
+        assert_eq!(
+            split("split(\"a\", 1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "split() expects two strings.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn native_substring() {
+        let substring = |code| evaluate(code).map(|value| value.to_string());
+
+        assert_eq!(
+            substring("substring(\"hello\", 1, 3)"),
+            Ok("el".to_string())
+        );
+        assert_eq!(
+            substring("substring(\"héllo\", 1, 3)"),
+            Ok("él".to_string())
+        );
+        assert_eq!(substring("substring(\"héllo\", 0, 1)"), Ok("h".to_string()));
+        assert_eq!(
+            substring("substring(\"héllo\", -2, 99)"),
+            Ok("héllo".to_string())
+        );
+        assert_eq!(substring("substring(\"héllo\", 7, 9)"), Ok("".to_string()));
+        assert_eq!(
+            substring("substring(\"hello\", 3, 1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "substring() start must not be after end.".to_string(),
+            })
+        );
+        assert_eq!(
+            substring("substring(\"hello\", 0.5, 1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "substring() expects a string and two integers.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn native_str_and_num() {
+        assert_eq!(evaluate("str(42)"), Ok(Value::Str("42".to_string())));
+        assert_eq!(
+            evaluate("str(nil) + str([true])"),
+            Ok(Value::Str("nil[true]".to_string()))
+        );
+        assert_eq!(evaluate("num(\"3.14\")"), Ok(Value::Number(3.14)));
+        assert_eq!(evaluate("num(\" -2 \")"), Ok(Value::Number(-2.0)));
+        assert_eq!(
+            evaluate("num(\"abc\")"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Can't convert 'abc' to a number.".to_string(),
+            })
+        );
+        assert_eq!(
+            evaluate("num(1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "num() expects a string.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn native_input() {
+        let mut lox = Lox::new();
+        let code = "var name = input(\"Name: \"); var rest = input(\"> \"); var end = input(\"\");";
+        let tokens = Scanner::new(&mut lox, code).scan();
+        let statements = Parser::new(&mut lox, tokens).parse();
+        let mut interpreter = Interpreter::with_output(vec![]);
+        interpreter.input = Box::new(Cursor::new("Ada\r\nlast line"));
+
+        assert_eq!(interpreter.interpret(&statements), Ok(()));
+        assert_eq!(
+            global(&interpreter, "name"),
+            Ok(Value::Str("Ada".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "rest"),
+            Ok(Value::Str("last line".to_string()))
+        );
+        assert_eq!(global(&interpreter, "end"), Ok(Value::Nil));
+        assert_eq!(String::from_utf8_lossy(&interpreter.out), "Name: > ");
+    }
+
+    #[test]
+    fn native_type() {
+        let (interpreter, result) = run("fun f() {} class A {} \
+             var types = [type(1), type(\"a\"), type(true), type(nil), type(f), type(clock), \
+             type(A), type(A()), type([]), type(type(1))];");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "types").unwrap().to_string(),
+            "[number, string, bool, nil, function, function, class, instance, list, string]"
+        );
+    }
+
+    #[test]
+    fn tail_recursion_does_not_grow_the_stack() {
+        let (interpreter, result) = run(
+            "fun count(n) { if (n == 0) return \"done\"; return count(n - 1); } \
+             var done = count(1000000);",
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "done"),
+            Ok(Value::Str("done".to_string()))
+        );
+    }
+
+    #[test]
+    fn tail_calls_keep_call_semantics() {
+        let (interpreter, result) = run("fun add(a, b) { return a + b; } \
+             fun show(x) { print x; } fun f(n) { while (true) { return add(n, 1); } } \
+             class A { init() { this.n = f(1); } get() { return f(this.n); } } \
+             fun main() { return show(a); } \
+             var a = f(1); var b = A().get(); main();");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(2.0)));
+        assert_eq!(global(&interpreter, "b"), Ok(Value::Number(3.0)));
+        assert_eq!(String::from_utf8_lossy(&interpreter.out), "2\n");
+    }
+
+    #[test]
+    fn unbounded_recursion_is_a_runtime_error() {
+        let overflow = Err(RuntimeError {
+            line: 1,
+            msg: "Stack overflow.".to_string(),
+        });
+        let code = "fun f(n) { return 1 + f(n + 1); } f(0);";
+        assert_eq!(run(code).1, overflow);
+
+        // The command line's depth on the command line's stack.
+        let result = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                let mut lox = Lox::new();
+                let tokens = Scanner::new(&mut lox, code).scan();
+                let statements = Parser::new(&mut lox, tokens).parse();
+                let options = InterpreterOptions {
+                    max_depth: MAX_DEPTH,
+                    ..InterpreterOptions::default()
+                };
+                let mut interpreter = Interpreter::with_options(vec![], options);
+                interpreter.resolve(Resolver::new(&mut lox).resolve(&statements));
+                interpreter.interpret(&statements)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(result, overflow);
+    }
+
+    #[test]
+    fn stack_depth_is_configurable() {
+        let mut lox = Lox::new();
+        let code = "fun f(n) { if (n > 0) f(n - 1); } f(2); f(3);";
+        let tokens = Scanner::new(&mut lox, code).scan();
+        let statements = Parser::new(&mut lox, tokens).parse();
+        let mut interpreter = Interpreter::with_options(
+            vec![],
+            InterpreterOptions {
+                max_depth: 3,
+                ..InterpreterOptions::default()
+            },
+        );
+        interpreter.resolve(Resolver::new(&mut lox).resolve(&statements));
+
+        assert_eq!(
+            interpreter.interpret(&statements),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Stack overflow.".to_string(),
+            })
+        );
+        // The depth unwinds with the error, so later calls start from zero.
+        assert_eq!(interpreter.depth, 0);
+    }
+
+    #[test]
+    fn native_fields_in_assignment_order() {
+        let (interpreter, result) = run("class A { init() { this.zeta = 1; this.alpha = 2; } } \
+             var a = A(); a.mid = 3; a.zeta = 4; var names = fields(a);");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "names").unwrap().to_string(),
+            "[zeta, alpha, mid]"
+        );
+        assert_eq!(
+            evaluate("fields(1)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "fields() expects an instance.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn native_copy_is_shallow() {
+        let (interpreter, result) = run("var inner = [1]; var list = [inner, 2]; \
+             var copied = copy(list); copied[1] = 3; copied[0][0] = 4; \
+             class A {} var a = A(); a.x = 1; var b = copy(a); b.x = 2; \
+             var fields = [a.x, b.x]; var same = copy(list) == list; var number = copy(5);");
+
+        assert_eq!(result, Ok(()));
+        let global = |name| global(&interpreter, name).unwrap().to_string();
+        assert_eq!(global("list"), "[[4], 2]");
+        assert_eq!(global("copied"), "[[4], 3]");
+        assert_eq!(global("fields"), "[1, 2]");
+        assert_eq!(global("same"), "false");
+        assert_eq!(global("number"), "5");
+    }
+
+    #[test]
+    fn native_assert() {
+        assert_eq!(evaluate("assert(1 < 2, \"unused\")"), Ok(Value::Nil));
+        assert_eq!(
+            evaluate("assert(1 > 2, \"1 is not greater than 2\")"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "1 is not greater than 2".to_string(),
+            })
+        );
+        assert_eq!(
+            evaluate("assert(nil, nil)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Assertion failed.".to_string(),
+            })
+        );
+        assert_eq!(evaluate("assert(true)"), Ok(Value::Nil));
+        assert_eq!(
+            evaluate("assert(false)"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Assertion failed.".to_string(),
+            })
+        );
+        assert_eq!(
+            evaluate("assert()"),
+            Err(RuntimeError {
+                line: 1,
+                msg: "Expected 1 to 2 arguments but got 0.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn long_else_if_chain() {
+        let chain: Vec<String> = (0..1000)
+            .map(|n| format!("if (x == {n}) r = {n};"))
+            .collect();
+        let code = format!("var x = 732; var r; {} else r = -1;", chain.join(" else "));
+        let (interpreter, result) = run(&code);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "r"), Ok(Value::Number(732.0)));
+
+        let code = code.replace("var x = 732;", "var x = 1000;");
+        let (interpreter, result) = run(&code);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "r"), Ok(Value::Number(-1.0)));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(evaluate("1 / 0"), Ok(Value::Number(f64::INFINITY)));
+        assert!(matches!(evaluate("1 % 0"), Ok(Value::Number(value)) if value.is_nan()));
+
+        let strict = |code: &str| {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+            let statements = Parser::new(&mut lox, tokens).parse();
+            let options = InterpreterOptions {
+                strict_division: true,
+                ..InterpreterOptions::default()
+            };
+            Interpreter::with_options(vec![], options).interpret(&statements)
+        };
+        let error = Err(RuntimeError {
+            line: 1,
+            msg: "Division by zero.".to_string(),
+        });
+
+        assert_eq!(strict("var zero = 0; print 1 / zero;"), error);
+        assert_eq!(strict("print 1 % 0;"), error);
+        assert_eq!(strict("print 6 / 3 + 1 % 2;"), Ok(()));
+    }
+
+    #[test]
+    fn switch_statement() {
+        let code = "fun name(n) { \
+             switch (n) { \
+               case 1: return \"one\"; \
+               case 1 + 1: print \"two\"; \
+               case 3: return \"three\"; \
+               default: return \"many\"; \
+             } \
+             return \"after\"; \
+           } \
+           var names = [name(1), name(2), name(3), name(4)];";
+        let (interpreter, result) = run(code);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            global(&interpreter, "names").unwrap().to_string(),
+            "[one, after, three, many]"
+        );
+        // Case two printed once and then left the switch, without falling
+        // into case three.
+        assert_eq!(String::from_utf8_lossy(&interpreter.out), "two\n");
+    }
+
+    #[test]
+    fn switch_without_match_or_default() {
+        let (interpreter, result) =
+            run("var a = \"x\"; switch (a) { case \"y\": a = 1; } { var b = a; a = b; }");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Str("x".to_string())));
+    }
+
+    #[test]
+    fn instances_display_through_to_string() {
+        let (interpreter, result) = run("class Point { \
+               init(x, y) { this.x = x; this.y = y; } \
+               toString() { return \"(\" + str(this.x) + \", \" + str(this.y) + \")\"; } \
+             } \
+             class Plain {} \
+             var p = Point(1, 2); print p; print Plain(); \
+             var text = str(p) + \" \" + str(Plain());");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            String::from_utf8_lossy(&interpreter.out),
+            "(1, 2)\n<instance Plain>\n"
+        );
+        assert_eq!(
+            global(&interpreter, "text"),
+            Ok(Value::Str("(1, 2) <instance Plain>".to_string()))
+        );
+    }
+
+    #[test]
+    fn lists_display_elements_through_to_string() {
+        let (interpreter, result) = run("class P { toString() { return \"p\"; } } \
+             class Plain {} \
+             print [P(), [P(), 1], Plain()]; \
+             var text = str([P()]);");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            String::from_utf8_lossy(&interpreter.out),
+            "[p, [p, 1], <instance Plain>]\n"
+        );
+        assert_eq!(
+            global(&interpreter, "text"),
+            Ok(Value::Str("[p]".to_string()))
+        );
+    }
+
+    #[test]
+    fn recursive_to_string_is_a_stack_overflow() {
+        let (_, result) = run("class A { toString() { print this; return \"a\"; } } print A();");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 1,
+                msg: "Stack overflow.".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn to_string_errors_are_runtime_errors() {
+        let (_, result) = run("class A { toString() { return -nil; } } print A();");
+
+        assert_eq!(
+            result,
+            Err(RuntimeError {
+                line: 1,
+                msg: "Operand must be a number.".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::lox::environment::Environment;
+use crate::lox::value::Value;
+use std::cell::RefCell;
+use std::io;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The interpreter's streams, for natives that talk to the user.
+pub trait Console {
+    fn write(&mut self, text: &str) -> io::Result<()>;
+
+    // `None` at end of input.
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+
+    // Display text of a value, which may run a `toString` method.
+    fn stringify(&mut self, value: &Value) -> Result<String, String>;
+}
+
+pub type NativeFn = fn(&mut dyn Console, &[Value]) -> Result<Value, String>;
+
+pub fn define_globals(environment: &mut Environment) {
+    define(environment, "clock", 0, clock);
+    define(environment, "len", 1, len);
+    define(environment, "str", 1, str);
+    define(environment, "num", 1, num);
+    define(environment, "input", 1, input);
+    define(environment, "type", 1, type_of);
+    define(environment, "upper", 1, upper);
+    define(environment, "lower", 1, lower);
+    define(environment, "trim", 1, trim);
+    define(environment, "split", 2, split);
+    define(environment, "substring", 3, substring);
+    define(environment, "fields", 1, fields);
+    define(environment, "copy", 1, copy);
+    define_optional(environment, "assert", 1..=2, assert);
+}
+
+fn define(environment: &mut Environment, name: &'static str, arity: usize, func: NativeFn) {
+    define_optional(environment, name, arity..=arity, func);
+}
+
+fn define_optional(
+    environment: &mut Environment,
+    name: &'static str,
+    arity: RangeInclusive<usize>,
+    func: NativeFn,
+) {
+    environment.define(name, Value::Native { name, arity, func });
+}
+
+fn clock(_: &mut dyn Console, _: &[Value]) -> Result<Value, String> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| "System clock is set before the Unix epoch.".to_string())?;
+
+    Ok(Value::Number(elapsed.as_secs_f64()))
+}
+
+// Strings count characters, not bytes.
+fn len(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    let count = match &arguments[0] {
+        Value::Str(value) => value.chars().count(),
+        Value::List(elements) => elements.borrow().len(),
+        _ => return Err("len() expects a string or list.".to_string()),
+    };
+
+    Ok(Value::Number(count as f64))
+}
+
+fn str(console: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    Ok(Value::Str(console.stringify(&arguments[0])?))
+}
+
+// Accepts surrounding whitespace but no `inf` or `NaN` spellings.
+fn num(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    let Value::Str(text) = &arguments[0] else {
+        return Err("num() expects a string.".to_string());
+    };
+
+    match text.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(Value::Number(value)),
+        _ => Err(format!("Can't convert '{text}' to a number.")),
+    }
+}
+
+// Prints the prompt as is and returns the next line without its line ending.
+fn input(console: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    let line = console
+        .write(&arguments[0].to_string())
+        .and_then(|_| console.read_line())
+        .map_err(|error| format!("Failed to read input: {error}."))?;
+
+    Ok(match line {
+        Some(line) => {
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            Value::Str(line.strip_suffix('\r').unwrap_or(line).to_string())
+        }
+        None => Value::Nil,
+    })
+}
+
+// One of "number", "string", "bool", "nil", "function", "class", "instance"
+// or "list".
+fn type_of(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    Ok(Value::Str(arguments[0].type_name().to_string()))
+}
+
+fn upper(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    Ok(Value::Str(string("upper", &arguments[0])?.to_uppercase()))
+}
+
+fn lower(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    Ok(Value::Str(string("lower", &arguments[0])?.to_lowercase()))
+}
+
+fn trim(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    Ok(Value::Str(
+        string("trim", &arguments[0])?.trim().to_string(),
+    ))
+}
+
+// An empty separator splits into characters.
+fn split(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    let (text, separator) = match (&arguments[0], &arguments[1]) {
+        (Value::Str(text), Value::Str(separator)) => (text, separator),
+        _ => return Err("split() expects two strings.".to_string()),
+    };
+
+    let parts: Vec<Value> = match separator.as_str() {
+        "" => text.chars().map(|c| Value::Str(c.to_string())).collect(),
+        _ => text
+            .split(separator.as_str())
+            .map(|part| Value::Str(part.to_string()))
+            .collect(),
+    };
+
+    Ok(Value::List(Rc::new(RefCell::new(parts))))
+}
+
+// Indices count characters and are clamped to the string.
+fn substring(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    let (text, start, end) = match (&arguments[0], &arguments[1], &arguments[2]) {
+        (Value::Str(text), Value::Number(start), Value::Number(end))
+            if start.fract() == 0.0 && end.fract() == 0.0 =>
+        {
+            (text, *start, *end)
+        }
+        _ => return Err("substring() expects a string and two integers.".to_string()),
+    };
+    if start > end {
+        return Err("substring() start must not be after end.".to_string());
+    }
+
+    // Saturating casts clamp negative indices to 0.
+    let (start, end) = (start as usize, end as usize);
+    Ok(Value::Str(text.chars().take(end).skip(start).collect()))
+}
+
+// Field names in assignment order, so output is reproducible.
+fn fields(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    let Value::Instance(instance) = &arguments[0] else {
+        return Err("fields() expects an instance.".to_string());
+    };
+
+    let names = instance
+        .borrow()
+        .field_names()
+        .iter()
+        .map(|name| Value::Str(name.clone()))
+        .collect();
+    Ok(Value::List(Rc::new(RefCell::new(names))))
+}
+
+// Shallow: the copy gets its own storage but shares nested lists and
+// instances with the original.
+fn copy(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    Ok(match &arguments[0] {
+        Value::List(elements) => Value::List(Rc::new(RefCell::new(elements.borrow().clone()))),
+        Value::Instance(instance) => {
+            Value::Instance(Rc::new(RefCell::new(instance.borrow().clone())))
+        }
+        value => value.clone(),
+    })
+}
+
+// A `nil` message falls back to a generic one.
+fn assert(_: &mut dyn Console, arguments: &[Value]) -> Result<Value, String> {
+    match (&arguments[0], arguments.get(1)) {
+        (condition, _) if condition.is_truthy() => Ok(Value::Nil),
+        (_, None | Some(Value::Nil)) => Err("Assertion failed.".to_string()),
+        (_, Some(message)) => Err(message.to_string()),
+    }
+}
+
+fn string<'a>(name: &str, value: &'a Value) -> Result<&'a str, String> {
+    match value {
+        Value::Str(text) => Ok(text),
+        _ => Err(format!("{name}() expects a string.")),
+    }
+}
@@ -0,0 +1,2094 @@
+use crate::lox::Reporter;
+use crate::lox::ast::{Expr, Stmt};
+use crate::lox::token::{Literal, Token, TokenKind};
+use std::rc::Rc;
+
+// Limit on call arguments and function parameters, as in reference Lox.
+const MAX_ARGS: usize = 255;
+
+const COMPARISON_OPERATORS: [TokenKind; 4] = [
+    TokenKind::Greater,
+    TokenKind::GreaterEqual,
+    TokenKind::Less,
+    TokenKind::LessEqual,
+];
+
+const COMPOUND_OPERATORS: [TokenKind; 4] = [
+    TokenKind::MinusEqual,
+    TokenKind::PlusEqual,
+    TokenKind::SlashEqual,
+    TokenKind::StarEqual,
+];
+
+struct ParseError;
+
+type ParseResult<T> = Result<T, ParseError>;
+
+pub struct Parser<'a, R>
+where
+    R: Reporter,
+{
+    reporter: &'a mut R,
+    tokens: Vec<Token>,
+    current: usize,
+    function_depth: usize,
+    loop_depth: usize,
+}
+
+impl<'a, R> Parser<'a, R>
+where
+    R: Reporter,
+{
+    pub fn new(reporter: &'a mut R, tokens: Vec<Token>) -> Self {
+        Self {
+            reporter,
+            tokens,
+            current: 0,
+            function_depth: 0,
+            loop_depth: 0,
+        }
+    }
+
+    pub fn parse(&mut self) -> Vec<Stmt> {
+        let mut statements = vec![];
+        while !self.done() {
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                Err(_) => self.synchronize(),
+            }
+        }
+
+        statements
+    }
+
+    fn declaration(&mut self) -> ParseResult<Stmt> {
+        if self.match_kinds(&[TokenKind::Class]) {
+            return self.class_declaration();
+        }
+        // A nameless `fun` starts a lambda expression statement instead.
+        if self.check(TokenKind::Fun) && self.check_next(TokenKind::Identifier) {
+            self.advance();
+            return self.function("function");
+        }
+        if self.match_kinds(&[TokenKind::Var]) {
+            return self.var_declaration();
+        }
+        if self.match_kinds(&[TokenKind::Const]) {
+            return self.const_declaration();
+        }
+
+        self.statement()
+    }
+
+    fn class_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = self
+            .consume(TokenKind::Identifier, "Expect class name.")?
+            .clone();
+
+        let superclass = if self.match_kinds(&[TokenKind::Less]) {
+            let name = self
+                .consume(TokenKind::Identifier, "Expect superclass name.")?
+                .clone();
+            Some(Expr::Variable(name))
+        } else {
+            None
+        };
+
+        self.consume(TokenKind::LeftBrace, "Expect '{' before class body.")?;
+
+        let mut methods = vec![];
+        while !self.check(TokenKind::RightBrace) && !self.done() {
+            methods.push(self.function("method")?);
+        }
+        self.consume(TokenKind::RightBrace, "Expect '}' after class body.")?;
+
+        Ok(Stmt::Class {
+            name,
+            superclass,
+            methods,
+        })
+    }
+
+    fn function(&mut self, kind: &str) -> ParseResult<Stmt> {
+        let name = self
+            .consume(TokenKind::Identifier, &format!("Expect {kind} name."))?
+            .clone();
+        self.consume(
+            TokenKind::LeftParen,
+            &format!("Expect '(' after {kind} name."),
+        )?;
+        let (params, body) = self.function_body(kind)?;
+
+        Ok(Stmt::Function { name, params, body })
+    }
+
+    // Parses from just after the opening '(' through the closing '}'.
+    fn function_body(&mut self, kind: &str) -> ParseResult<(Vec<Token>, Rc<[Stmt]>)> {
+        let mut params = vec![];
+        if !self.check(TokenKind::RightParen) {
+            loop {
+                if params.len() >= MAX_ARGS {
+                    self.error(&format!("Can't have more than {MAX_ARGS} parameters."));
+                }
+                params.push(
+                    self.consume(TokenKind::Identifier, "Expect parameter name.")?
+                        .clone(),
+                );
+
+                // A trailing comma is allowed before the closing paren.
+                if !self.match_kinds(&[TokenKind::Comma]) || self.check(TokenKind::RightParen) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenKind::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(
+            TokenKind::LeftBrace,
+            &format!("Expect '{{' before {kind} body."),
+        )?;
+        // Loops don't reach into nested functions.
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        self.function_depth += 1;
+        let body = self.block();
+        self.function_depth -= 1;
+        self.loop_depth = loop_depth;
+
+        Ok((params, body?.into()))
+    }
+
+    fn var_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = self
+            .consume(TokenKind::Identifier, "Expect variable name.")?
+            .clone();
+
+        let initializer = if self.match_kinds(&[TokenKind::Equal]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        self.consume(
+            TokenKind::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+        Ok(Stmt::Var { name, initializer })
+    }
+
+    // Constants must be initialized, they can't be assigned later.
+    fn const_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = self
+            .consume(TokenKind::Identifier, "Expect constant name.")?
+            .clone();
+        self.consume(TokenKind::Equal, "Expect '=' after constant name.")?;
+        let initializer = self.expression()?;
+
+        self.consume(
+            TokenKind::Semicolon,
+            "Expect ';' after constant declaration.",
+        )?;
+        Ok(Stmt::Const { name, initializer })
+    }
+
+    fn statement(&mut self) -> ParseResult<Stmt> {
+        if self.match_kinds(&[TokenKind::Break]) {
+            return self.loop_jump("break").map(Stmt::Break);
+        }
+        if self.match_kinds(&[TokenKind::Continue]) {
+            return self.loop_jump("continue").map(Stmt::Continue);
+        }
+        if self.match_kinds(&[TokenKind::For]) {
+            return self.for_statement();
+        }
+        if self.match_kinds(&[TokenKind::If]) {
+            return self.if_statement();
+        }
+        if self.match_kinds(&[TokenKind::Print]) {
+            return self.print_statement();
+        }
+        if self.match_kinds(&[TokenKind::Return]) {
+            return self.return_statement();
+        }
+        if self.match_kinds(&[TokenKind::Switch]) {
+            return self.switch_statement();
+        }
+        if self.match_kinds(&[TokenKind::While]) {
+            return self.while_statement();
+        }
+        if self.match_kinds(&[TokenKind::LeftBrace]) {
+            return Ok(Stmt::Block(self.block()?));
+        }
+
+        self.expression_statement()
+    }
+
+    fn loop_jump(&mut self, keyword: &str) -> ParseResult<Token> {
+        let token = self.previous().clone();
+        if self.loop_depth == 0 {
+            self.error_at(
+                self.current - 1,
+                &format!("Can't use '{keyword}' outside of a loop."),
+            );
+        }
+
+        self.consume(
+            TokenKind::Semicolon,
+            &format!("Expect ';' after '{keyword}'."),
+        )?;
+        Ok(token)
+    }
+
+    // Desugars into a while loop, wrapped in a block only when there is an
+    // initializer to scope.
+    fn for_statement(&mut self) -> ParseResult<Stmt> {
+        self.consume(TokenKind::LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.match_kinds(&[TokenKind::Semicolon]) {
+            None
+        } else if self.match_kinds(&[TokenKind::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(TokenKind::Semicolon) {
+            Expr::Literal(Literal::Bool(true))
+        } else {
+            self.expression()?
+        };
+        self.consume(TokenKind::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check(TokenKind::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenKind::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.loop_body()?;
+        let mut body = Stmt::While {
+            condition,
+            body: Box::new(body),
+            increment,
+        };
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(vec![initializer, body]);
+        }
+
+        Ok(body)
+    }
+
+    // `else if` chains are parsed in a loop, so long ones can't overflow the
+    // stack, and nested back to front.
+    fn if_statement(&mut self) -> ParseResult<Stmt> {
+        let mut branches = vec![];
+        let mut else_branch = loop {
+            self.consume(TokenKind::LeftParen, "Expect '(' after 'if'.")?;
+            let condition = self.expression()?;
+            self.consume(TokenKind::RightParen, "Expect ')' after if condition.")?;
+            branches.push((condition, Box::new(self.statement()?)));
+
+            // A dangling else binds to the nearest if.
+            if !self.match_kinds(&[TokenKind::Else]) {
+                break None;
+            }
+            if !self.match_kinds(&[TokenKind::If]) {
+                break Some(Box::new(self.statement()?));
+            }
+        };
+
+        while let Some((condition, then_branch)) = branches.pop() {
+            else_branch = Some(Box::new(Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            }));
+        }
+
+        Ok(*else_branch.expect("An if statement has at least one branch."))
+    }
+
+    fn print_statement(&mut self) -> ParseResult<Stmt> {
+        let value = self.expression()?;
+        self.consume(TokenKind::Semicolon, "Expect ';' after value.")?;
+        Ok(Stmt::Print(value))
+    }
+
+    fn return_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
+        if self.function_depth == 0 {
+            self.error_at(self.current - 1, "Can't return from top-level code.");
+        }
+
+        let value = if self.check(TokenKind::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenKind::Semicolon, "Expect ';' after return value.")?;
+
+        Ok(Stmt::Return { keyword, value })
+    }
+
+    fn while_statement(&mut self) -> ParseResult<Stmt> {
+        self.consume(TokenKind::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenKind::RightParen, "Expect ')' after condition.")?;
+        let body = Box::new(self.loop_body()?);
+
+        Ok(Stmt::While {
+            condition,
+            body,
+            increment: None,
+        })
+    }
+
+    fn loop_body(&mut self) -> ParseResult<Stmt> {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+
+        body
+    }
+
+    fn switch_statement(&mut self) -> ParseResult<Stmt> {
+        self.consume(TokenKind::LeftParen, "Expect '(' after 'switch'.")?;
+        let value = self.expression()?;
+        self.consume(TokenKind::RightParen, "Expect ')' after switch value.")?;
+        self.consume(TokenKind::LeftBrace, "Expect '{' before switch body.")?;
+
+        let mut cases = vec![];
+        let mut default = None;
+        while !self.check(TokenKind::RightBrace) && !self.done() {
+            if self.match_kinds(&[TokenKind::Case]) {
+                let case = self.expression()?;
+                self.consume(TokenKind::Colon, "Expect ':' after case value.")?;
+                cases.push((case, self.case_body()?));
+            } else if self.match_kinds(&[TokenKind::Default]) {
+                if default.is_some() {
+                    self.error_at(self.current - 1, "Can't have more than one default case.");
+                }
+                self.consume(TokenKind::Colon, "Expect ':' after 'default'.")?;
+                default = Some(self.case_body()?);
+            } else {
+                return Err(self.error("Expect 'case' or 'default' in switch."));
+            }
+        }
+        self.consume(TokenKind::RightBrace, "Expect '}' after switch body.")?;
+
+        Ok(Stmt::Switch {
+            value,
+            cases,
+            default,
+        })
+    }
+
+    // Statements up to the next case label or the end of the switch.
+    fn case_body(&mut self) -> ParseResult<Vec<Stmt>> {
+        let mut statements = vec![];
+        while !self.check(TokenKind::Case)
+            && !self.check(TokenKind::Default)
+            && !self.check(TokenKind::RightBrace)
+            && !self.done()
+        {
+            statements.push(self.declaration()?);
+        }
+
+        Ok(statements)
+    }
+
+    fn block(&mut self) -> ParseResult<Vec<Stmt>> {
+        let mut statements = vec![];
+        while !self.check(TokenKind::RightBrace) && !self.done() {
+            statements.push(self.declaration()?);
+        }
+
+        self.consume(TokenKind::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn expression_statement(&mut self) -> ParseResult<Stmt> {
+        let expr = self.expression()?;
+        self.consume(TokenKind::Semicolon, "Expect ';' after expression.")?;
+        Ok(Stmt::Expression(expr))
+    }
+
+    fn expression(&mut self) -> ParseResult<Expr> {
+        self.assignment()
+    }
+
+    fn assignment(&mut self) -> ParseResult<Expr> {
+        let expr = self.ternary()?;
+
+        if self.match_kinds(&[TokenKind::Equal]) {
+            let equals = self.current - 1;
+            let value = self.assignment()?;
+
+            match expr {
+                Expr::Variable(name) => {
+                    return Ok(Expr::Assign {
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expr::Get { object, name } => {
+                    return Ok(Expr::Set {
+                        object,
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                } => {
+                    return Ok(Expr::IndexSet {
+                        object,
+                        bracket,
+                        index,
+                        value: Box::new(value),
+                    });
+                }
+                _ => (),
+            }
+
+            // Report without unwinding, the parser is not confused.
+            self.error_at(equals, "Invalid assignment target.");
+        }
+
+        if self.match_kinds(&COMPOUND_OPERATORS) {
+            return self.compound_assignment(expr);
+        }
+
+        Ok(expr)
+    }
+
+    // Desugars `a += b` into `a = a + b`. A property or index target's
+    // operands are evaluated twice, once to read and once to store.
+    fn compound_assignment(&mut self, target: Expr) -> ParseResult<Expr> {
+        let operator_index = self.current - 1;
+        let compound = self.previous().clone();
+        let value = self.assignment()?;
+
+        let (kind, lexeme) = match compound.kind {
+            TokenKind::MinusEqual => (TokenKind::Minus, "-"),
+            TokenKind::PlusEqual => (TokenKind::Plus, "+"),
+            TokenKind::SlashEqual => (TokenKind::Slash, "/"),
+            _ => (TokenKind::Star, "*"),
+        };
+        let operator = Token {
+            kind,
+            lexeme: lexeme.to_string(),
+            end: compound.start + 1,
+            ..compound
+        };
+        let binary = |left: Expr| Expr::Binary {
+            left: Box::new(left),
+            operator,
+            right: Box::new(value),
+        };
+
+        match target {
+            Expr::Variable(name) => Ok(Expr::Assign {
+                name: name.clone(),
+                value: Box::new(binary(Expr::Variable(name))),
+            }),
+            Expr::Get { object, name } => Ok(Expr::Set {
+                value: Box::new(binary(Expr::Get {
+                    object: object.clone(),
+                    name: name.clone(),
+                })),
+                object,
+                name,
+            }),
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => Ok(Expr::IndexSet {
+                value: Box::new(binary(Expr::Index {
+                    object: object.clone(),
+                    bracket: bracket.clone(),
+                    index: index.clone(),
+                })),
+                object,
+                bracket,
+                index,
+            }),
+            target => {
+                self.error_at(operator_index, "Invalid assignment target.");
+                Ok(target)
+            }
+        }
+    }
+
+    // Right-associative: `a ? b : c ? d : e` nests in the else branch.
+    fn ternary(&mut self) -> ParseResult<Expr> {
+        let condition = self.or()?;
+        if !self.match_kinds(&[TokenKind::Question]) {
+            return Ok(condition);
+        }
+
+        let then_branch = self.expression()?;
+        self.consume(
+            TokenKind::Colon,
+            "Expect ':' after then branch of conditional expression.",
+        )?;
+        let else_branch = self.ternary()?;
+
+        Ok(Expr::Ternary {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        })
+    }
+
+    fn or(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.and()?;
+
+        while self.match_kinds(&[TokenKind::Or]) {
+            let operator = self.previous().clone();
+            let right = self.and()?;
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.equality()?;
+
+        while self.match_kinds(&[TokenKind::And]) {
+            let operator = self.previous().clone();
+            let right = self.equality()?;
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.bit_or()?;
+
+        while self.match_kinds(&[TokenKind::BangEqual, TokenKind::EqualEqual]) {
+            let operator = self.previous().clone();
+            let right = self.bit_or()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    // Bitwise operators bind tighter than equality but looser than comparison.
+    fn bit_or(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.bit_xor()?;
+
+        while self.match_kinds(&[TokenKind::Pipe]) {
+            let operator = self.previous().clone();
+            let right = self.bit_xor()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_xor(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.bit_and()?;
+
+        while self.match_kinds(&[TokenKind::Caret]) {
+            let operator = self.previous().clone();
+            let right = self.bit_and()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.comparison()?;
+
+        while self.match_kinds(&[TokenKind::Ampersand]) {
+            let operator = self.previous().clone();
+            let right = self.comparison()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.shift()?;
+
+        while self.match_kinds(&COMPARISON_OPERATORS) {
+            let operator = self.previous().clone();
+            // Explicit grouping such as `(a < b) < c` is still accepted.
+            if let Expr::Binary { operator: left, .. } = &expr
+                && COMPARISON_OPERATORS.contains(&left.kind)
+            {
+                self.error_at(self.current - 1, "Chained comparisons are not supported.");
+            }
+            let right = self.shift()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    // Shifts sit between comparison and addition, as in C.
+    fn shift(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.term()?;
+
+        while self.match_kinds(&[TokenKind::LessLess, TokenKind::GreaterGreater]) {
+            let operator = self.previous().clone();
+            let right = self.term()?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.factor()?;
+
+        while self.match_kinds(&[TokenKind::Minus, TokenKind::Plus]) {
+            let operator = self.previous().clone();
+            let right = self.factor()?;
+            expr = fold(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.unary()?;
+
+        while self.match_kinds(&[TokenKind::Slash, TokenKind::Star, TokenKind::Percent]) {
+            let operator = self.previous().clone();
+            let right = self.unary()?;
+            expr = fold(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> ParseResult<Expr> {
+        if self.match_kinds(&[TokenKind::Bang, TokenKind::Minus, TokenKind::Tilde]) {
+            let operator = self.previous().clone();
+            let right = self.unary()?;
+            return Ok(Expr::Unary {
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        if self.match_kinds(&[TokenKind::PlusPlus, TokenKind::MinusMinus]) {
+            let operator_index = self.current - 1;
+            let target = self.unary()?;
+            return Ok(self.increment(target, operator_index, true));
+        }
+
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> ParseResult<Expr> {
+        let expr = self.call()?;
+
+        if self.match_kinds(&[TokenKind::PlusPlus, TokenKind::MinusMinus]) {
+            return Ok(self.increment(expr, self.current - 1, false));
+        }
+
+        Ok(expr)
+    }
+
+    fn increment(&mut self, target: Expr, operator_index: usize, prefix: bool) -> Expr {
+        match target {
+            Expr::Variable(name) => Expr::Increment {
+                name,
+                operator: self.tokens[operator_index].clone(),
+                prefix,
+            },
+            target => {
+                // Report without unwinding, the parser is not confused.
+                self.error_at(operator_index, "Invalid increment target.");
+                target
+            }
+        }
+    }
+
+    fn call(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.match_kinds(&[TokenKind::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_kinds(&[TokenKind::Dot]) {
+                let name = self
+                    .consume(TokenKind::Identifier, "Expect property name after '.'.")?
+                    .clone();
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else if self.match_kinds(&[TokenKind::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self
+                    .consume(TokenKind::RightBracket, "Expect ']' after index.")?
+                    .clone();
+                expr = Expr::Index {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                };
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> ParseResult<Expr> {
+        let mut arguments = vec![];
+        if !self.check(TokenKind::RightParen) {
+            loop {
+                if arguments.len() >= MAX_ARGS {
+                    self.error(&format!("Can't have more than {MAX_ARGS} arguments."));
+                }
+                arguments.push(self.expression()?);
+
+                if !self.match_kinds(&[TokenKind::Comma]) || self.check(TokenKind::RightParen) {
+                    break;
+                }
+            }
+        }
+
+        let paren = self
+            .consume(TokenKind::RightParen, "Expect ')' after arguments.")?
+            .clone();
+
+        Ok(Expr::Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        })
+    }
+
+    fn list(&mut self) -> ParseResult<Expr> {
+        let mut elements = vec![];
+        if !self.check(TokenKind::RightBracket) {
+            loop {
+                elements.push(self.expression()?);
+
+                if !self.match_kinds(&[TokenKind::Comma]) || self.check(TokenKind::RightBracket) {
+                    break;
+                }
+            }
+        }
+
+        let bracket = self
+            .consume(TokenKind::RightBracket, "Expect ']' after list elements.")?
+            .clone();
+
+        Ok(Expr::ArrayLiteral { bracket, elements })
+    }
+
+    fn primary(&mut self) -> ParseResult<Expr> {
+        if self.match_kinds(&[
+            TokenKind::False,
+            TokenKind::True,
+            TokenKind::Nil,
+            TokenKind::Number,
+            TokenKind::String,
+            TokenKind::Char,
+        ]) {
+            // An embedder's extra keyword may claim a literal kind without a value.
+            let Some(literal) = self.previous().literal.clone() else {
+                return Err(self.error_at(self.current - 1, "Expect expression."));
+            };
+            return Ok(Expr::Literal(literal));
+        }
+
+        if self.match_kinds(&[TokenKind::LeftBracket]) {
+            return self.list();
+        }
+
+        if self.match_kinds(&[TokenKind::Fun]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenKind::LeftParen, "Expect '(' after 'fun'.")?;
+            let (params, body) = self.function_body("function")?;
+            return Ok(Expr::Lambda {
+                keyword,
+                params,
+                body,
+            });
+        }
+
+        if self.match_kinds(&[TokenKind::Super]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenKind::Dot, "Expect '.' after 'super'.")?;
+            let method = self
+                .consume(TokenKind::Identifier, "Expect superclass method name.")?
+                .clone();
+            return Ok(Expr::Super { keyword, method });
+        }
+
+        if self.match_kinds(&[TokenKind::This]) {
+            return Ok(Expr::This(self.previous().clone()));
+        }
+
+        if self.match_kinds(&[TokenKind::Identifier]) {
+            return Ok(Expr::Variable(self.previous().clone()));
+        }
+
+        if self.match_kinds(&[TokenKind::LeftParen]) {
+            let expr = self.expression()?;
+            self.consume(TokenKind::RightParen, "Expect ')' after expression.")?;
+            return Ok(Expr::Grouping(Box::new(expr)));
+        }
+
+        Err(self.error("Expect expression."))
+    }
+
+    // Skips to the next statement boundary so later errors still get reported.
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.done() {
+            if self.previous().kind == TokenKind::Semicolon {
+                return;
+            }
+
+            match self.peek().kind {
+                TokenKind::Class
+                | TokenKind::Fun
+                | TokenKind::Var
+                | TokenKind::Const
+                | TokenKind::For
+                | TokenKind::If
+                | TokenKind::Switch
+                | TokenKind::While
+                | TokenKind::Print
+                | TokenKind::Return => return,
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    fn consume(&mut self, kind: TokenKind, msg: &str) -> ParseResult<&Token> {
+        if self.check(kind) {
+            return Ok(self.advance());
+        }
+
+        Err(self.error(msg))
+    }
+
+    fn error(&mut self, msg: &str) -> ParseError {
+        self.error_at(self.current, msg)
+    }
+
+    fn error_at(&mut self, index: usize, msg: &str) -> ParseError {
+        let token = &self.tokens[index];
+        let info = if token.kind == TokenKind::Eof {
+            " at end".to_string()
+        } else {
+            format!(" at '{}'", token.lexeme)
+        };
+        self.reporter.report(token.line, token.column, &info, msg);
+
+        ParseError
+    }
+
+    fn match_kinds(&mut self, kinds: &[TokenKind]) -> bool {
+        if kinds.iter().any(|kind| self.check(*kind)) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn check(&self, kind: TokenKind) -> bool {
+        self.peek().kind == kind
+    }
+
+    fn check_next(&self, kind: TokenKind) -> bool {
+        self.tokens
+            .get(self.current + 1)
+            .is_some_and(|token| token.kind == kind)
+    }
+
+    fn advance(&mut self) -> &Token {
+        if !self.done() {
+            self.current += 1;
+        }
+        self.previous()
+    }
+
+    fn done(&self) -> bool {
+        self.peek().kind == TokenKind::Eof
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
+    }
+
+    fn previous(&self) -> &Token {
+        &self.tokens[self.current - 1]
+    }
+}
+
+// Folds arithmetic on two literals into a single literal, so `2 * 3` parses as
+// `6`. Division and remainder by zero are left for the interpreter.
+fn fold(left: Expr, operator: Token, right: Expr) -> Expr {
+    let folded = match (literal(&left), operator.kind, literal(&right)) {
+        (Some(Literal::Number(left)), kind, Some(Literal::Number(right))) => match kind {
+            TokenKind::Plus => Some(left + right),
+            TokenKind::Minus => Some(left - right),
+            TokenKind::Star => Some(left * right),
+            TokenKind::Slash if *right != 0.0 => Some(left / right),
+            TokenKind::Percent if *right != 0.0 => Some(left % right),
+            _ => None,
+        }
+        .map(Literal::Number),
+        (Some(Literal::Str(left)), TokenKind::Plus, Some(Literal::Str(right))) => {
+            Some(Literal::Str(format!("{left}{right}")))
+        }
+        _ => None,
+    };
+
+    match folded {
+        Some(literal) => Expr::Literal(literal),
+        None => Expr::Binary {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        },
+    }
+}
+
+fn literal(expr: &Expr) -> Option<&Literal> {
+    match expr {
+        Expr::Literal(literal) => Some(literal),
+        Expr::Grouping(expr) => literal(expr),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lox::Lox;
+    use crate::lox::diagnostic::{Diagnostic, Severity};
+    use crate::lox::printer::AstPrinter;
+    use crate::lox::scanner::Scanner;
+
+    fn parse(lox: &mut Lox, code: &str) -> Vec<Stmt> {
+        let tokens = Scanner::new(lox, code).scan();
+        Parser::new(lox, tokens).parse()
+    }
+
+    fn parse_expr(lox: &mut Lox, code: &str) -> Option<Expr> {
+        let tokens = Scanner::new(lox, code).scan();
+        Parser::new(lox, tokens).expression().ok()
+    }
+
+    fn token(kind: TokenKind, column: u32, lexeme: &str) -> Token {
+        Token {
+            kind,
+            line: 1,
+            column,
+            start: column as usize - 1,
+            end: column as usize - 1 + lexeme.len(),
+            lexeme: lexeme.to_string(),
+            literal: None,
+        }
+    }
+
+    fn number(value: f64) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Number(value)))
+    }
+
+    fn variable(column: u32, name: &str) -> Box<Expr> {
+        Box::new(Expr::Variable(token(TokenKind::Identifier, column, name)))
+    }
+
+    #[test]
+    fn precedence() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "a + b * c");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Binary {
+                left: variable(1, "a"),
+                operator: token(TokenKind::Plus, 3, "+"),
+                right: Box::new(Expr::Binary {
+                    left: variable(5, "b"),
+                    operator: token(TokenKind::Star, 7, "*"),
+                    right: variable(9, "c"),
+                }),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn grouping() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "(a + b)");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Grouping(Box::new(Expr::Binary {
+                left: variable(2, "a"),
+                operator: token(TokenKind::Plus, 4, "+"),
+                right: variable(6, "b"),
+            })))
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn unary_and_equality() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "!true == -1 < 2");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Binary {
+                left: Box::new(Expr::Unary {
+                    operator: token(TokenKind::Bang, 1, "!"),
+                    right: Box::new(Expr::Literal(Literal::Bool(true))),
+                }),
+                operator: token(TokenKind::EqualEqual, 7, "=="),
+                right: Box::new(Expr::Binary {
+                    left: Box::new(Expr::Unary {
+                        operator: token(TokenKind::Minus, 10, "-"),
+                        right: number(1.0),
+                    }),
+                    operator: token(TokenKind::Less, 13, "<"),
+                    right: number(2.0),
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn missing_closing_paren() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "(1 + 2");
+
+        assert_eq!(expr, None);
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 7,
+                info: " at end".to_string(),
+                msg: "Expect ')' after expression.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_expression() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "1 + ;");
+
+        assert_eq!(expr, None);
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 5,
+                info: " at ';'".to_string(),
+                msg: "Expect expression.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn print_statement() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "print 1;");
+
+        assert_eq!(statements, vec![Stmt::Print(*number(1.0))]);
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn expression_statement() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "a + b;");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Expression(Expr::Binary {
+                left: variable(1, "a"),
+                operator: token(TokenKind::Plus, 3, "+"),
+                right: variable(5, "b"),
+            })]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn missing_semicolon() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "print 1;\nprint 2\n");
+
+        assert_eq!(statements, vec![Stmt::Print(*number(1.0))]);
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 3,
+                column: 1,
+                info: " at end".to_string(),
+                msg: "Expect ';' after value.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn var_declaration() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "var a = 1; var b; print a;");
+
+        assert_eq!(
+            statements,
+            vec![
+                Stmt::Var {
+                    name: token(TokenKind::Identifier, 5, "a"),
+                    initializer: Some(*number(1.0)),
+                },
+                Stmt::Var {
+                    name: token(TokenKind::Identifier, 16, "b"),
+                    initializer: None,
+                },
+                Stmt::Print(Expr::Variable(token(TokenKind::Identifier, 25, "a"))),
+            ]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn const_declaration() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "const a = 1; const b;");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Const {
+                name: token(TokenKind::Identifier, 7, "a"),
+                initializer: *number(1.0),
+            }]
+        );
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 21,
+                info: " at ';'".to_string(),
+                msg: "Expect '=' after constant name.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_variable_name() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "var 1 = 2;");
+
+        assert_eq!(statements, vec![]);
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 5,
+                info: " at '1'".to_string(),
+                msg: "Expect variable name.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn assignment() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "a = b = 1;");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Expression(Expr::Assign {
+                name: token(TokenKind::Identifier, 1, "a"),
+                value: Box::new(Expr::Assign {
+                    name: token(TokenKind::Identifier, 5, "b"),
+                    value: number(1.0),
+                }),
+            })]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn invalid_assignment_target() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "1 = 2;");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 3,
+                info: " at '='".to_string(),
+                msg: "Invalid assignment target.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn block() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "{ var a; { a; } }");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Block(vec![
+                Stmt::Var {
+                    name: token(TokenKind::Identifier, 7, "a"),
+                    initializer: None,
+                },
+                Stmt::Block(vec![Stmt::Expression(Expr::Variable(token(
+                    TokenKind::Identifier,
+                    12,
+                    "a"
+                )))]),
+            ])]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn unclosed_block() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "{ print 1;");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 11,
+                info: " at end".to_string(),
+                msg: "Expect '}' after block.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn dangling_else() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "if (true) if (false) 1; else 2;");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::If {
+                condition: Expr::Literal(Literal::Bool(true)),
+                then_branch: Box::new(Stmt::If {
+                    condition: Expr::Literal(Literal::Bool(false)),
+                    then_branch: Box::new(Stmt::Expression(*number(1.0))),
+                    else_branch: Some(Box::new(Stmt::Expression(*number(2.0)))),
+                }),
+                else_branch: None,
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn while_statement() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "while (true) print 1;");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::While {
+                condition: Expr::Literal(Literal::Bool(true)),
+                body: Box::new(Stmt::Print(*number(1.0))),
+                increment: None,
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn for_statement_desugars_to_while() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "for (var i = 0; i < 1; i = 1) 2;");
+
+        let i = |column| Expr::Variable(token(TokenKind::Identifier, column, "i"));
+        assert_eq!(
+            statements,
+            vec![Stmt::Block(vec![
+                Stmt::Var {
+                    name: token(TokenKind::Identifier, 10, "i"),
+                    initializer: Some(*number(0.0)),
+                },
+                Stmt::While {
+                    condition: Expr::Binary {
+                        left: Box::new(i(17)),
+                        operator: token(TokenKind::Less, 19, "<"),
+                        right: number(1.0),
+                    },
+                    body: Box::new(Stmt::Expression(*number(2.0))),
+                    increment: Some(Expr::Assign {
+                        name: token(TokenKind::Identifier, 24, "i"),
+                        value: number(1.0),
+                    }),
+                },
+            ])]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn for_statement_without_clauses() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "for (;;) 1;");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::While {
+                condition: Expr::Literal(Literal::Bool(true)),
+                body: Box::new(Stmt::Expression(*number(1.0))),
+                increment: None,
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn logical_precedence() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "1 or 2 and 3 == 4");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Logical {
+                left: number(1.0),
+                operator: token(TokenKind::Or, 3, "or"),
+                right: Box::new(Expr::Logical {
+                    left: number(2.0),
+                    operator: token(TokenKind::And, 8, "and"),
+                    right: Box::new(Expr::Binary {
+                        left: number(3.0),
+                        operator: token(TokenKind::EqualEqual, 14, "=="),
+                        right: number(4.0),
+                    }),
+                }),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn function_declaration() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "fun add(a, b) { print a; }");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Function {
+                name: token(TokenKind::Identifier, 5, "add"),
+                params: vec![
+                    token(TokenKind::Identifier, 9, "a"),
+                    token(TokenKind::Identifier, 12, "b"),
+                ],
+                body: vec![Stmt::Print(Expr::Variable(token(
+                    TokenKind::Identifier,
+                    23,
+                    "a"
+                )))]
+                .into(),
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn call_chain() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "f(1, 2)()");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Call {
+                callee: Box::new(Expr::Call {
+                    callee: Box::new(Expr::Variable(token(TokenKind::Identifier, 1, "f"))),
+                    paren: token(TokenKind::RightParen, 7, ")"),
+                    arguments: vec![*number(1.0), *number(2.0)],
+                }),
+                paren: token(TokenKind::RightParen, 9, ")"),
+                arguments: vec![],
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn mixed_call_chain() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "f(a)(b).c()[d]").unwrap();
+
+        assert_eq!(
+            AstPrinter.print(&expr),
+            "(index (call (. c (call (call f a) b))) d)"
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn missing_parameter_name() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "fun f(a, 1) {}");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 10,
+                info: " at '1'".to_string(),
+                msg: "Expect parameter name.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn return_statement() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "fun f() { return 1; return; }");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Function {
+                name: token(TokenKind::Identifier, 5, "f"),
+                params: vec![],
+                body: vec![
+                    Stmt::Return {
+                        keyword: token(TokenKind::Return, 11, "return"),
+                        value: Some(*number(1.0)),
+                    },
+                    Stmt::Return {
+                        keyword: token(TokenKind::Return, 21, "return"),
+                        value: None,
+                    },
+                ]
+                .into(),
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn top_level_return() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "fun f() {}\nreturn 1;");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 2,
+                column: 1,
+                info: " at 'return'".to_string(),
+                msg: "Can't return from top-level code.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn class_declaration() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "class A { m() {} }");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Class {
+                name: token(TokenKind::Identifier, 7, "A"),
+                superclass: None,
+                methods: vec![Stmt::Function {
+                    name: token(TokenKind::Identifier, 11, "m"),
+                    params: vec![],
+                    body: vec![].into(),
+                }],
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn property_get_and_set() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "a.b.c = 1");
+
+        let a = Expr::Variable(token(TokenKind::Identifier, 1, "a"));
+        assert_eq!(
+            expr,
+            Some(Expr::Set {
+                object: Box::new(Expr::Get {
+                    object: Box::new(a),
+                    name: token(TokenKind::Identifier, 3, "b"),
+                }),
+                name: token(TokenKind::Identifier, 5, "c"),
+                value: number(1.0),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn class_with_superclass() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "class B < A {}");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Class {
+                name: token(TokenKind::Identifier, 7, "B"),
+                superclass: Some(Expr::Variable(token(TokenKind::Identifier, 11, "A"))),
+                methods: vec![],
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn super_access() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "super.m");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Super {
+                keyword: token(TokenKind::Super, 1, "super"),
+                method: token(TokenKind::Identifier, 7, "m"),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn synchronize_reports_every_error() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "var = 1; print 2; print (3; var a = 4;");
+
+        assert_eq!(
+            statements,
+            vec![
+                Stmt::Print(*number(2.0)),
+                Stmt::Var {
+                    name: token(TokenKind::Identifier, 33, "a"),
+                    initializer: Some(*number(4.0)),
+                },
+            ]
+        );
+        assert_eq!(
+            lox.take_errors(),
+            vec![
+                Diagnostic {
+                    line: 1,
+                    column: 5,
+                    info: " at '='".to_string(),
+                    msg: "Expect variable name.".to_string(),
+                    severity: Severity::Error,
+                },
+                Diagnostic {
+                    line: 1,
+                    column: 27,
+                    info: " at ';'".to_string(),
+                    msg: "Expect ')' after expression.".to_string(),
+                    severity: Severity::Error,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn modulo_has_factor_precedence() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "a + b % c");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Binary {
+                left: variable(1, "a"),
+                operator: token(TokenKind::Plus, 3, "+"),
+                right: Box::new(Expr::Binary {
+                    left: variable(5, "b"),
+                    operator: token(TokenKind::Percent, 7, "%"),
+                    right: variable(9, "c"),
+                }),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn nested_ternary_is_right_associative() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "1 ? 2 : 3 ? 4 : 5");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Ternary {
+                condition: number(1.0),
+                then_branch: number(2.0),
+                else_branch: Box::new(Expr::Ternary {
+                    condition: number(3.0),
+                    then_branch: number(4.0),
+                    else_branch: number(5.0),
+                }),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn ternary_missing_colon() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "true ? 1 2");
+
+        assert_eq!(expr, None);
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 10,
+                info: " at '2'".to_string(),
+                msg: "Expect ':' after then branch of conditional expression.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn chained_comparison() {
+        let mut lox = Lox::new();
+        parse_expr(&mut lox, "1 < 2 < 3");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 7,
+                info: " at '<'".to_string(),
+                msg: "Chained comparisons are not supported.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+
+        parse_expr(&mut lox, "1 < 2");
+        parse_expr(&mut lox, "(1 < 2) == (2 >= 1)");
+        assert!(lox.take_errors().is_empty());
+    }
+
+    #[test]
+    fn break_and_continue() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "while (true) { continue; break; }");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::While {
+                condition: Expr::Literal(Literal::Bool(true)),
+                body: Box::new(Stmt::Block(vec![
+                    Stmt::Continue(token(TokenKind::Continue, 16, "continue")),
+                    Stmt::Break(token(TokenKind::Break, 26, "break")),
+                ])),
+                increment: None,
+            }]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn break_outside_loop() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "break;\nwhile (true) { fun f() { continue; } }");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![
+                Diagnostic {
+                    line: 1,
+                    column: 1,
+                    info: " at 'break'".to_string(),
+                    msg: "Can't use 'break' outside of a loop.".to_string(),
+                    severity: Severity::Error,
+                },
+                Diagnostic {
+                    line: 2,
+                    column: 26,
+                    info: " at 'continue'".to_string(),
+                    msg: "Can't use 'continue' outside of a loop.".to_string(),
+                    severity: Severity::Error,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lambda_expression() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "fun (a) { return a; };");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Expression(Expr::Lambda {
+                keyword: token(TokenKind::Fun, 1, "fun"),
+                params: vec![token(TokenKind::Identifier, 6, "a")],
+                body: vec![Stmt::Return {
+                    keyword: token(TokenKind::Return, 11, "return"),
+                    value: Some(Expr::Variable(token(TokenKind::Identifier, 18, "a"))),
+                }]
+                .into(),
+            })]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let mut lox = Lox::new();
+        let statements = parse(&mut lox, "a -= 1;");
+
+        assert_eq!(
+            statements,
+            vec![Stmt::Expression(Expr::Assign {
+                name: token(TokenKind::Identifier, 1, "a"),
+                value: Box::new(Expr::Binary {
+                    left: Box::new(Expr::Variable(token(TokenKind::Identifier, 1, "a"))),
+                    operator: token(TokenKind::Minus, 3, "-"),
+                    right: number(1.0),
+                }),
+            })]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn invalid_compound_assignment_target() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "1 += 2;");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 3,
+                info: " at '+='".to_string(),
+                msg: "Invalid assignment target.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn increment() {
+        let mut lox = Lox::new();
+
+        assert_eq!(
+            parse_expr(&mut lox, "a++"),
+            Some(Expr::Increment {
+                name: token(TokenKind::Identifier, 1, "a"),
+                operator: token(TokenKind::PlusPlus, 2, "++"),
+                prefix: false,
+            })
+        );
+        assert_eq!(
+            parse_expr(&mut lox, "--a"),
+            Some(Expr::Increment {
+                name: token(TokenKind::Identifier, 3, "a"),
+                operator: token(TokenKind::MinusMinus, 1, "--"),
+                prefix: true,
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn invalid_increment_target() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "++5;");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 1,
+                info: " at '++'".to_string(),
+                msg: "Invalid increment target.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let mut lox = Lox::new();
+        let with_comma = parse(&mut lox, "fun g(a, b,) {} g(1, 2,);");
+        let without_comma = parse(&mut lox, "fun g(a, b) {} g(1, 2);");
+
+        let [
+            Stmt::Function { params, .. },
+            Stmt::Expression(Expr::Call { arguments, .. }),
+        ] = with_comma.as_slice()
+        else {
+            panic!("Expected a declaration and a call.");
+        };
+        assert_eq!(params.len(), 2);
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(
+            AstPrinter.print_stmt(&with_comma[1]),
+            AstPrinter.print_stmt(&without_comma[1])
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn lone_comma_in_call() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "f(,);");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 3,
+                info: " at ','".to_string(),
+                msg: "Expect expression.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn lone_comma_in_parameters() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "fun g(,) {}");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 7,
+                info: " at ','".to_string(),
+                msg: "Expect parameter name.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn argument_limit() {
+        let call = |count: usize| format!("f({});", vec!["1"; count].join(","));
+        let mut lox = Lox::new();
+
+        parse(&mut lox, &call(MAX_ARGS));
+        assert!(!lox.has_error);
+
+        let statements = parse(&mut lox, &call(MAX_ARGS + 1));
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 513,
+                info: " at '1'".to_string(),
+                msg: "Can't have more than 255 arguments.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+        // The call is still parsed.
+        assert_eq!(statements.len(), 1);
+    }
+
+    #[test]
+    fn parameter_limit() {
+        let function = |count: usize| {
+            let params: Vec<_> = (0..count).map(|i| format!("p{i}")).collect();
+            format!("fun f({}) {{}}", params.join(","))
+        };
+        let mut lox = Lox::new();
+
+        parse(&mut lox, &function(MAX_ARGS));
+        assert!(!lox.has_error);
+
+        parse(&mut lox, &function(MAX_ARGS + 1));
+        let errors = lox.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].info, " at 'p255'");
+        assert_eq!(errors[0].msg, "Can't have more than 255 parameters.");
+    }
+
+    #[test]
+    fn list_and_index() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "[1, 2][0]");
+
+        assert_eq!(
+            expr,
+            Some(Expr::Index {
+                object: Box::new(Expr::ArrayLiteral {
+                    bracket: token(TokenKind::RightBracket, 6, "]"),
+                    elements: vec![
+                        Expr::Literal(Literal::Number(1.0)),
+                        Expr::Literal(Literal::Number(2.0))
+                    ],
+                }),
+                bracket: token(TokenKind::RightBracket, 9, "]"),
+                index: number(0.0),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn unclosed_list() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "[1, 2;");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 6,
+                info: " at ';'".to_string(),
+                msg: "Expect ']' after list elements.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn index_assignment() {
+        let mut lox = Lox::new();
+        let expr = parse_expr(&mut lox, "a[0] = 1");
+
+        assert_eq!(
+            expr,
+            Some(Expr::IndexSet {
+                object: Box::new(Expr::Variable(token(TokenKind::Identifier, 1, "a"))),
+                bracket: token(TokenKind::RightBracket, 4, "]"),
+                index: number(0.0),
+                value: number(1.0),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn constant_folding() {
+        let mut lox = Lox::new();
+
+        assert_eq!(parse_expr(&mut lox, "2 * 3 + 1"), Some(*number(7.0)));
+        assert_eq!(parse_expr(&mut lox, "(1 + 2) * 3"), Some(*number(9.0)));
+        assert_eq!(
+            parse_expr(&mut lox, "\"a\" + \"b\""),
+            Some(Expr::Literal(Literal::Str("ab".to_string())))
+        );
+        assert_eq!(
+            parse_expr(&mut lox, "a + 1"),
+            Some(Expr::Binary {
+                left: variable(1, "a"),
+                operator: token(TokenKind::Plus, 3, "+"),
+                right: number(1.0),
+            })
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn constant_folding_keeps_runtime_errors() {
+        let mut lox = Lox::new();
+
+        for code in ["1 / 0", "1 % 0", "1 + \"a\"", "\"a\" * 2"] {
+            assert!(
+                matches!(parse_expr(&mut lox, code), Some(Expr::Binary { .. })),
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn switch_errors() {
+        let mut lox = Lox::new();
+        parse(&mut lox, "switch (a) { default: 1; default: 2; }");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 26,
+                info: " at 'default'".to_string(),
+                msg: "Can't have more than one default case.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+
+        let mut lox = Lox::new();
+        parse(&mut lox, "switch (a) { print 1; }");
+
+        assert_eq!(
+            lox.take_errors().first(),
+            Some(&Diagnostic {
+                line: 1,
+                column: 14,
+                info: " at 'print'".to_string(),
+                msg: "Expect 'case' or 'default' in switch.".to_string(),
+                severity: Severity::Error,
+            })
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use crate::lox::ast::{Expr, Stmt};
+use crate::lox::token::{Literal, Token};
+
+pub struct AstPrinter;
+
+impl AstPrinter {
+    pub fn print_stmt(&self, stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::Break(_) => "(break)".to_string(),
+            Stmt::Const { name, initializer } => {
+                self.parenthesize(&format!("const {}", name.lexeme), &[initializer])
+            }
+            Stmt::Continue(_) => "(continue)".to_string(),
+            Stmt::Block(statements) => {
+                let mut output = "(block".to_string();
+                for stmt in statements {
+                    output.push(' ');
+                    output.push_str(&self.print_stmt(stmt));
+                }
+                output.push(')');
+
+                output
+            }
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+            } => {
+                let mut output = format!("(class {}", name.lexeme);
+                if let Some(superclass) = superclass {
+                    output.push_str(&format!(" < {}", self.print(superclass)));
+                }
+                for method in methods {
+                    output.push(' ');
+                    output.push_str(&self.print_stmt(method));
+                }
+                output.push(')');
+
+                output
+            }
+            Stmt::Expression(expr) => self.parenthesize(";", &[expr]),
+            Stmt::Function { name, params, body } => {
+                self.function(&format!("fun {}", name.lexeme), params, body)
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let mut output = format!(
+                    "(if {} {}",
+                    self.print(condition),
+                    self.print_stmt(then_branch)
+                );
+                if let Some(else_branch) = else_branch {
+                    output.push(' ');
+                    output.push_str(&self.print_stmt(else_branch));
+                }
+                output.push(')');
+
+                output
+            }
+            Stmt::Print(expr) => self.parenthesize("print", &[expr]),
+            Stmt::Return { value, .. } => match value {
+                Some(value) => self.parenthesize("return", &[value]),
+                None => "(return)".to_string(),
+            },
+            Stmt::Switch {
+                value,
+                cases,
+                default,
+            } => {
+                let mut output = format!("(switch {}", self.print(value));
+                for (case, body) in cases {
+                    output.push_str(&format!(" (case {}", self.print(case)));
+                    output.push_str(&self.body(body));
+                }
+                if let Some(default) = default {
+                    output.push_str(" (default");
+                    output.push_str(&self.body(default));
+                }
+                output.push(')');
+
+                output
+            }
+            Stmt::Var { name, initializer } => match initializer {
+                Some(initializer) => {
+                    self.parenthesize(&format!("var {}", name.lexeme), &[initializer])
+                }
+                None => format!("(var {})", name.lexeme),
+            },
+            Stmt::While {
+                condition,
+                body,
+                increment,
+            } => match increment {
+                Some(increment) => format!(
+                    "(while {} {} {})",
+                    self.print(condition),
+                    self.print_stmt(body),
+                    self.print(increment)
+                ),
+                None => format!(
+                    "(while {} {})",
+                    self.print(condition),
+                    self.print_stmt(body)
+                ),
+            },
+        }
+    }
+
+    pub fn print(&self, expr: &Expr) -> String {
+        match expr {
+            Expr::ArrayLiteral { elements, .. } => {
+                let elements: Vec<&Expr> = elements.iter().collect();
+                self.parenthesize("list", &elements)
+            }
+            Expr::Assign { name, value } => {
+                self.parenthesize(&format!("= {}", name.lexeme), &[value])
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+            } => self.parenthesize(&operator.lexeme, &[left, right]),
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                let mut exprs = vec![callee.as_ref()];
+                exprs.extend(arguments);
+                self.parenthesize("call", &exprs)
+            }
+            Expr::Get { object, name } => {
+                self.parenthesize(&format!(". {}", name.lexeme), &[object])
+            }
+            Expr::Grouping(expr) => self.parenthesize("group", &[expr]),
+            Expr::Increment {
+                name,
+                operator,
+                prefix,
+            } => match prefix {
+                true => format!("({} {})", operator.lexeme, name.lexeme),
+                false => format!("({} {})", name.lexeme, operator.lexeme),
+            },
+            Expr::Index { object, index, .. } => self.parenthesize("index", &[object, index]),
+            Expr::IndexSet {
+                object,
+                index,
+                value,
+                ..
+            } => self.parenthesize("=index", &[object, index, value]),
+            Expr::Lambda { params, body, .. } => self.function("fun", params, body),
+            Expr::Literal(literal) => self.literal(literal),
+            Expr::Logical {
+                left,
+                operator,
+                right,
+            } => self.parenthesize(&operator.lexeme, &[left, right]),
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => self.parenthesize(&format!("=. {}", name.lexeme), &[object, value]),
+            Expr::Super { method, .. } => format!("(super {})", method.lexeme),
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+            } => self.parenthesize("?:", &[condition, then_branch, else_branch]),
+            Expr::This(_) => "this".to_string(),
+            Expr::Unary { operator, right } => self.parenthesize(&operator.lexeme, &[right]),
+            Expr::Variable(name) => name.lexeme.clone(),
+        }
+    }
+
+    fn function(&self, name: &str, params: &[Token], body: &[Stmt]) -> String {
+        let params: Vec<&str> = params.iter().map(|param| param.lexeme.as_str()).collect();
+        let mut output = format!("({name} ({})", params.join(" "));
+        for stmt in body {
+            output.push(' ');
+            output.push_str(&self.print_stmt(stmt));
+        }
+        output.push(')');
+
+        output
+    }
+
+    // Space separated statements and a closing paren.
+    fn body(&self, statements: &[Stmt]) -> String {
+        let mut output = String::new();
+        for stmt in statements {
+            output.push(' ');
+            output.push_str(&self.print_stmt(stmt));
+        }
+        output.push(')');
+
+        output
+    }
+
+    fn literal(&self, literal: &Literal) -> String {
+        match literal {
+            Literal::Number(value) => value.to_string(),
+            Literal::Str(value) => value.clone(),
+            Literal::Char(value) => value.to_string(),
+            Literal::Bool(value) => value.to_string(),
+            Literal::Nil => "nil".to_string(),
+        }
+    }
+
+    fn parenthesize(&self, name: &str, exprs: &[&Expr]) -> String {
+        let mut output = format!("({name}");
+        for expr in exprs {
+            output.push(' ');
+            output.push_str(&self.print(expr));
+        }
+        output.push(')');
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lox::token::TokenKind;
+
+    fn token(kind: TokenKind, lexeme: &str) -> Token {
+        Token {
+            kind,
+            line: 1,
+            column: 1,
+            start: 0,
+            end: lexeme.len(),
+            lexeme: lexeme.to_string(),
+            literal: None,
+        }
+    }
+
+    #[test]
+    fn print_nested_expression() {
+        let expr = Expr::Binary {
+            left: Box::new(Expr::Unary {
+                operator: token(TokenKind::Minus, "-"),
+                right: Box::new(Expr::Literal(Literal::Number(123.0))),
+            }),
+            operator: token(TokenKind::Star, "*"),
+            right: Box::new(Expr::Grouping(Box::new(Expr::Literal(Literal::Number(
+                45.67,
+            ))))),
+        };
+
+        assert_eq!(AstPrinter.print(&expr), "(* (- 123) (group 45.67))");
+    }
+
+    #[test]
+    fn print_literals() {
+        let expr = Expr::Binary {
+            left: Box::new(Expr::Literal(Literal::Str("text".to_string()))),
+            operator: token(TokenKind::EqualEqual, "=="),
+            right: Box::new(Expr::Binary {
+                left: Box::new(Expr::Literal(Literal::Bool(true))),
+                operator: token(TokenKind::BangEqual, "!="),
+                right: Box::new(Expr::Literal(Literal::Nil)),
+            }),
+        };
+
+        assert_eq!(AstPrinter.print(&expr), "(== text (!= true nil))");
+    }
+
+    #[test]
+    fn print_statements() {
+        let value = Expr::Literal(Literal::Number(1.0));
+
+        assert_eq!(
+            AstPrinter.print_stmt(&Stmt::Print(value.clone())),
+            "(print 1)"
+        );
+        assert_eq!(AstPrinter.print_stmt(&Stmt::Expression(value)), "(; 1)");
+    }
+
+    #[test]
+    fn print_var_statements() {
+        let name = token(TokenKind::Identifier, "a");
+
+        assert_eq!(
+            AstPrinter.print_stmt(&Stmt::Var {
+                name: name.clone(),
+                initializer: Some(Expr::Literal(Literal::Number(1.0))),
+            }),
+            "(var a 1)"
+        );
+        assert_eq!(
+            AstPrinter.print_stmt(&Stmt::Var {
+                name: name.clone(),
+                initializer: None,
+            }),
+            "(var a)"
+        );
+        assert_eq!(AstPrinter.print(&Expr::Variable(name)), "a");
+    }
+
+    #[test]
+    fn print_assignment() {
+        let expr = Expr::Assign {
+            name: token(TokenKind::Identifier, "a"),
+            value: Box::new(Expr::Literal(Literal::Number(2.0))),
+        };
+
+        assert_eq!(AstPrinter.print(&expr), "(= a 2)");
+    }
+
+    #[test]
+    fn print_block() {
+        let block = Stmt::Block(vec![
+            Stmt::Print(Expr::Literal(Literal::Number(1.0))),
+            Stmt::Block(vec![]),
+        ]);
+
+        assert_eq!(AstPrinter.print_stmt(&block), "(block (print 1) (block))");
+    }
+
+    #[test]
+    fn print_if() {
+        let stmt = Stmt::If {
+            condition: Expr::Literal(Literal::Bool(true)),
+            then_branch: Box::new(Stmt::Print(Expr::Literal(Literal::Number(1.0)))),
+            else_branch: Some(Box::new(Stmt::Print(Expr::Literal(Literal::Number(2.0))))),
+        };
+
+        assert_eq!(
+            AstPrinter.print_stmt(&stmt),
+            "(if true (print 1) (print 2))"
+        );
+    }
+
+    #[test]
+    fn print_while() {
+        let stmt = Stmt::While {
+            condition: Expr::Literal(Literal::Bool(true)),
+            body: Box::new(Stmt::Block(vec![])),
+            increment: None,
+        };
+
+        assert_eq!(AstPrinter.print_stmt(&stmt), "(while true (block))");
+    }
+
+    #[test]
+    fn print_logical() {
+        let expr = Expr::Logical {
+            left: Box::new(Expr::Literal(Literal::Nil)),
+            operator: token(TokenKind::Or, "or"),
+            right: Box::new(Expr::Literal(Literal::Number(2.0))),
+        };
+
+        assert_eq!(AstPrinter.print(&expr), "(or nil 2)");
+    }
+
+    #[test]
+    fn print_function_and_call() {
+        let name = token(TokenKind::Identifier, "f");
+        let function = Stmt::Function {
+            name: name.clone(),
+            params: vec![token(TokenKind::Identifier, "a")],
+            body: vec![Stmt::Print(Expr::Variable(token(
+                TokenKind::Identifier,
+                "a",
+            )))]
+            .into(),
+        };
+        let call = Expr::Call {
+            callee: Box::new(Expr::Variable(name)),
+            paren: token(TokenKind::RightParen, ")"),
+            arguments: vec![Expr::Literal(Literal::Number(1.0))],
+        };
+
+        assert_eq!(AstPrinter.print_stmt(&function), "(fun f (a) (print a))");
+        assert_eq!(AstPrinter.print(&call), "(call f 1)");
+    }
+
+    #[test]
+    fn print_class_and_properties() {
+        let object = Box::new(Expr::Variable(token(TokenKind::Identifier, "a")));
+        let class = Stmt::Class {
+            name: token(TokenKind::Identifier, "A"),
+            superclass: Some(Expr::Variable(token(TokenKind::Identifier, "B"))),
+            methods: vec![Stmt::Function {
+                name: token(TokenKind::Identifier, "m"),
+                params: vec![],
+                body: vec![].into(),
+            }],
+        };
+        let set = Expr::Set {
+            object: object.clone(),
+            name: token(TokenKind::Identifier, "b"),
+            value: Box::new(Expr::Get {
+                object,
+                name: token(TokenKind::Identifier, "c"),
+            }),
+        };
+
+        assert_eq!(AstPrinter.print_stmt(&class), "(class A < B (fun m ()))");
+        assert_eq!(AstPrinter.print(&set), "(=. b a (. c a))");
+    }
+
+    #[test]
+    fn print_lambda() {
+        let lambda = Expr::Lambda {
+            keyword: token(TokenKind::Fun, "fun"),
+            params: vec![token(TokenKind::Identifier, "a")],
+            body: vec![Stmt::Print(Expr::Variable(token(
+                TokenKind::Identifier,
+                "a",
+            )))]
+            .into(),
+        };
+
+        assert_eq!(AstPrinter.print(&lambda), "(fun (a) (print a))");
+    }
+}
@@ -0,0 +1,547 @@
+use crate::lox::Reporter;
+use crate::lox::ast::{Expr, Stmt};
+use crate::lox::token::Token;
+use std::collections::HashMap;
+
+// Scope distance of every variable access, `None` for globals. Keyed by node
+// address, so the AST must not move between resolving and interpreting.
+pub type Locals = HashMap<*const Expr, Option<usize>>;
+
+#[derive(PartialEq, Copy, Clone)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+pub struct Resolver<'a, R>
+where
+    R: Reporter,
+{
+    reporter: &'a mut R,
+    // Declared names per block, `false` until the initializer is resolved.
+    scopes: Vec<HashMap<String, bool>>,
+    // Local variables of each block that have not been read yet.
+    unused: Vec<HashMap<String, Token>>,
+    locals: Locals,
+    current_class: ClassType,
+}
+
+impl<'a, R> Resolver<'a, R>
+where
+    R: Reporter,
+{
+    pub fn new(reporter: &'a mut R) -> Self {
+        Self {
+            reporter,
+            scopes: vec![],
+            unused: vec![],
+            locals: HashMap::new(),
+            current_class: ClassType::None,
+        }
+    }
+
+    pub fn resolve(mut self, statements: &[Stmt]) -> Locals {
+        self.resolve_stmts(statements);
+        self.locals
+    }
+
+    fn resolve_stmts(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block(statements) => self.resolve_block(statements),
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+            } => {
+                let enclosing_class = self.current_class;
+                self.current_class = ClassType::Class;
+                self.declare(name);
+                self.define(name);
+
+                if let Some(superclass) = superclass {
+                    self.current_class = ClassType::Subclass;
+                    if let Expr::Variable(superclass_name) = superclass
+                        && superclass_name.lexeme == name.lexeme
+                    {
+                        self.error(superclass_name, "A class can't inherit from itself.");
+                    }
+                    self.resolve_expr(superclass);
+
+                    self.begin_scope();
+                    self.define_name("super");
+                }
+
+                self.begin_scope();
+                self.define_name("this");
+                for method in methods {
+                    if let Stmt::Function { params, body, .. } = method {
+                        self.resolve_function(params, body);
+                    }
+                }
+                self.end_scope();
+
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+                self.current_class = enclosing_class;
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => (),
+            Stmt::Expression(expr) | Stmt::Print(expr) => self.resolve_expr(expr),
+            Stmt::Function { name, params, body } => {
+                self.declare(name);
+                self.define(name);
+                self.resolve_function(params, body);
+            }
+            Stmt::If { .. } => {
+                // Walks `else if` chains in a loop instead of recursing.
+                let mut stmt = stmt;
+                while let Stmt::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } = stmt
+                {
+                    self.resolve_expr(condition);
+                    self.resolve_stmt(then_branch);
+                    match else_branch {
+                        Some(else_branch) => stmt = else_branch,
+                        None => return,
+                    }
+                }
+                self.resolve_stmt(stmt);
+            }
+            Stmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    self.resolve_expr(value);
+                }
+            }
+            Stmt::Switch {
+                value,
+                cases,
+                default,
+            } => {
+                self.resolve_expr(value);
+                for (case, body) in cases {
+                    self.resolve_expr(case);
+                    self.resolve_block(body);
+                }
+                if let Some(default) = default {
+                    self.resolve_block(default);
+                }
+            }
+            Stmt::Const { name, initializer } => self.resolve_variable(name, Some(initializer)),
+            Stmt::Var { name, initializer } => self.resolve_variable(name, initializer.as_ref()),
+            Stmt::While {
+                condition,
+                body,
+                increment,
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(body);
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment);
+                }
+            }
+        }
+    }
+
+    fn resolve_block(&mut self, statements: &[Stmt]) {
+        self.begin_scope();
+        self.resolve_stmts(statements);
+        self.end_scope();
+    }
+
+    fn resolve_variable(&mut self, name: &Token, initializer: Option<&Expr>) {
+        self.declare(name);
+        if let Some(initializer) = initializer {
+            self.resolve_expr(initializer);
+        }
+        self.define(name);
+        if let Some(unused) = self.unused.last_mut() {
+            unused.insert(name.lexeme.clone(), name.clone());
+        }
+    }
+
+    fn resolve_function(&mut self, params: &[Token], body: &[Stmt]) {
+        self.begin_scope();
+        for param in params {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_stmts(body);
+        self.end_scope();
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::ArrayLiteral { elements, .. } => {
+                for element in elements {
+                    self.resolve_expr(element);
+                }
+            }
+            Expr::Assign { name, value } => {
+                self.resolve_expr(value);
+                self.resolve_local(expr, name);
+            }
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                self.resolve_expr(callee);
+                for argument in arguments {
+                    self.resolve_expr(argument);
+                }
+            }
+            Expr::Get { object, .. } => self.resolve_expr(object),
+            Expr::Grouping(expr) => self.resolve_expr(expr),
+            Expr::Increment { name, .. } => {
+                self.mark_used(name);
+                self.resolve_local(expr, name);
+            }
+            Expr::Index { object, index, .. } => {
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+            }
+            Expr::IndexSet {
+                object,
+                index,
+                value,
+                ..
+            } => {
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+                self.resolve_expr(value);
+            }
+            Expr::Lambda { params, body, .. } => self.resolve_function(params, body),
+            Expr::Literal(_) => (),
+            Expr::Set { object, value, .. } => {
+                self.resolve_expr(value);
+                self.resolve_expr(object);
+            }
+            Expr::Super { keyword, .. } => match self.current_class {
+                ClassType::None => self.error(keyword, "Can't use 'super' outside of a class."),
+                ClassType::Class => {
+                    self.error(keyword, "Can't use 'super' in a class with no superclass.")
+                }
+                ClassType::Subclass => self.resolve_local(expr, keyword),
+            },
+            Expr::Ternary {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_expr(then_branch);
+                self.resolve_expr(else_branch);
+            }
+            Expr::This(keyword) => {
+                if self.current_class == ClassType::None {
+                    self.error(keyword, "Can't use 'this' outside of a class.");
+                    return;
+                }
+
+                self.resolve_local(expr, keyword);
+            }
+            Expr::Unary { right, .. } => self.resolve_expr(right),
+            Expr::Variable(name) => {
+                let declared = self.scopes.last().and_then(|scope| scope.get(&name.lexeme));
+                if declared == Some(&false) {
+                    self.error(name, "Can't read local variable in its own initializer.");
+                }
+
+                self.resolve_local(expr, name);
+                self.mark_used(name);
+            }
+        }
+    }
+
+    fn resolve_local(&mut self, expr: &Expr, name: &Token) {
+        let depth = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(&name.lexeme));
+        self.locals.insert(expr, depth);
+    }
+
+    // Only reads count as uses, assigning to a variable does not.
+    fn mark_used(&mut self, name: &Token) {
+        let scope = self
+            .scopes
+            .iter()
+            .rposition(|scope| scope.contains_key(&name.lexeme));
+        if let Some(scope) = scope {
+            self.unused[scope].remove(&name.lexeme);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.unused.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+
+        let unused = self.unused.pop().expect("Scope was begun.");
+        let mut unused: Vec<Token> = unused.into_values().collect();
+        // Report in source order, not hash order.
+        unused.sort_by_key(|name| name.start);
+        for name in unused {
+            let msg = format!("Local variable '{}' is never used.", name.lexeme);
+            self.reporter.warning(name.line, name.column, &msg);
+        }
+    }
+
+    // Globals may be redeclared, locals may not.
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        if scope.insert(name.lexeme.clone(), false).is_some() {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        self.define_name(&name.lexeme);
+    }
+
+    fn define_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), true);
+        }
+    }
+
+    fn error(&mut self, token: &Token, msg: &str) {
+        let info = format!(" at '{}'", token.lexeme);
+        self.reporter.report(token.line, token.column, &info, msg);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lox::Lox;
+    use crate::lox::diagnostic::{Diagnostic, Severity};
+    use crate::lox::parser::Parser;
+    use crate::lox::scanner::Scanner;
+
+    #[derive(Default)]
+    struct TestReporter {
+        errors: Vec<String>,
+        warnings: Vec<String>,
+    }
+
+    impl Reporter for TestReporter {
+        fn report(&mut self, line: u32, column: u32, info: &str, msg: &str) {
+            self.errors
+                .push(format!("[line {line}, column {column}] Error{info}: {msg}"));
+        }
+
+        fn report_runtime(&mut self, line: u32, msg: &str) {
+            self.errors.push(format!("{msg}\n[line {line}]"));
+        }
+
+        fn warning(&mut self, line: u32, column: u32, msg: &str) {
+            self.warnings
+                .push(format!("[line {line}, column {column}] Warning: {msg}"));
+        }
+    }
+
+    fn warnings(code: &str) -> Vec<String> {
+        let mut reporter = TestReporter::default();
+        let tokens = Scanner::new(&mut reporter, code).scan();
+        let statements = Parser::new(&mut reporter, tokens).parse();
+        Resolver::new(&mut reporter).resolve(&statements);
+
+        assert!(reporter.errors.is_empty());
+        reporter.warnings
+    }
+
+    fn resolve(lox: &mut Lox, code: &str) -> Vec<Option<usize>> {
+        let tokens = Scanner::new(lox, code).scan();
+        let statements = Parser::new(lox, tokens).parse();
+        let locals = Resolver::new(lox).resolve(&statements);
+
+        let mut depths: Vec<_> = locals.into_values().collect();
+        depths.sort();
+        depths
+    }
+
+    #[test]
+    fn scope_distances() {
+        let mut lox = Lox::new();
+        let depths = resolve(&mut lox, "var g; { var a; { a; g; } a = 1; }");
+
+        assert_eq!(depths, vec![None, Some(0), Some(1)]);
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn parameters_are_local_to_the_function() {
+        let mut lox = Lox::new();
+        let depths = resolve(&mut lox, "fun f(a) { { a; } }");
+
+        assert_eq!(depths, vec![Some(1)]);
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn read_local_in_own_initializer() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "var a = 1; { var a = a; print a; }");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 22,
+                info: " at 'a'".to_string(),
+                msg: "Can't read local variable in its own initializer.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn global_initializer_may_shadow() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "var a = 1; var a = a;");
+
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn duplicate_local_declaration() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "{ var a = 1; var a = 2; print a; }");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 18,
+                info: " at 'a'".to_string(),
+                msg: "Already a variable with this name in this scope.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn global_redeclaration_is_allowed() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "var a = 1; var a = 2; { var a; } { var a; }");
+
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn this_resolves_to_class_scope() {
+        let mut lox = Lox::new();
+        let depths = resolve(&mut lox, "class A { m() { this; } }");
+
+        assert_eq!(depths, vec![Some(1)]);
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn this_outside_class() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "fun f() { this; }");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 11,
+                info: " at 'this'".to_string(),
+                msg: "Can't use 'this' outside of a class.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn class_inherits_from_itself() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "class A < A {}");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 11,
+                info: " at 'A'".to_string(),
+                msg: "A class can't inherit from itself.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn super_outside_class() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "super.m();");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 1,
+                info: " at 'super'".to_string(),
+                msg: "Can't use 'super' outside of a class.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn super_without_superclass() {
+        let mut lox = Lox::new();
+        resolve(&mut lox, "class A { m() { super.m(); } }");
+
+        assert_eq!(
+            lox.take_errors(),
+            vec![Diagnostic {
+                line: 1,
+                column: 17,
+                info: " at 'super'".to_string(),
+                msg: "Can't use 'super' in a class with no superclass.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn unused_local_warning() {
+        assert_eq!(
+            warnings("{ var used = 1; var unused = 2; print used; }"),
+            vec!["[line 1, column 21] Warning: Local variable 'unused' is never used."]
+        );
+    }
+
+    #[test]
+    fn globals_parameters_and_reads_are_not_warned() {
+        let code = "var global; fun f(param) { var a; var b = 1; a = b; { print a; } } \
+                    class A { m() { return this; } }";
+
+        assert!(warnings(code).is_empty());
+        assert!(warnings("{ var i = 0; print i++; }").is_empty());
+        assert_eq!(
+            warnings("{ var a; a = 1; }"),
+            vec!["[line 1, column 7] Warning: Local variable 'a' is never used."]
+        );
+    }
+}
@@ -1,35 +1,58 @@
-use crate::lox::token::{Token, TokenKind};
 use crate::lox::Reporter;
+use crate::lox::diagnostic::Diagnostic;
+use crate::lox::token::{Literal, Token, TokenKind};
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
-fn is_digit(char: &u8) -> bool {
-    matches!(char, b'0'..=b'9')
+fn is_digit(char: char) -> bool {
+    char.is_ascii_digit()
 }
 
-fn is_alphanumeric(char: &u8) -> bool {
-    matches!(char, b'_' | b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z')
+fn is_alpha(char: char) -> bool {
+    char == '_' || char.is_alphabetic()
 }
 
-fn keywords() -> HashMap<&'static str, TokenKind> {
-    let mut keywords = HashMap::new();
-    keywords.insert("and", TokenKind::And);
-    keywords.insert("class", TokenKind::Class);
-    keywords.insert("else", TokenKind::Else);
-    keywords.insert("false", TokenKind::False);
-    keywords.insert("fun", TokenKind::Fun);
-    keywords.insert("for", TokenKind::For);
-    keywords.insert("if", TokenKind::If);
-    keywords.insert("nil", TokenKind::Nil);
-    keywords.insert("or", TokenKind::Or);
-    keywords.insert("print", TokenKind::Print);
-    keywords.insert("return", TokenKind::Return);
-    keywords.insert("super", TokenKind::Super);
-    keywords.insert("this", TokenKind::This);
-    keywords.insert("true", TokenKind::True);
-    keywords.insert("var", TokenKind::Var);
-    keywords.insert("while", TokenKind::While);
-
-    keywords
+fn is_alphanumeric(char: char) -> bool {
+    is_alpha(char) || is_digit(char)
+}
+
+// Built once and shared by every scanner, the REPL scans once per line.
+pub fn keywords() -> &'static HashMap<&'static str, TokenKind> {
+    static KEYWORDS: OnceLock<HashMap<&'static str, TokenKind>> = OnceLock::new();
+    KEYWORDS.get_or_init(|| {
+        HashMap::from([
+            ("and", TokenKind::And),
+            ("break", TokenKind::Break),
+            ("case", TokenKind::Case),
+            ("class", TokenKind::Class),
+            ("const", TokenKind::Const),
+            ("continue", TokenKind::Continue),
+            ("default", TokenKind::Default),
+            ("else", TokenKind::Else),
+            ("false", TokenKind::False),
+            ("fun", TokenKind::Fun),
+            ("for", TokenKind::For),
+            ("if", TokenKind::If),
+            ("nil", TokenKind::Nil),
+            ("or", TokenKind::Or),
+            ("print", TokenKind::Print),
+            ("return", TokenKind::Return),
+            ("super", TokenKind::Super),
+            ("switch", TokenKind::Switch),
+            ("this", TokenKind::This),
+            ("true", TokenKind::True),
+            ("var", TokenKind::Var),
+            ("while", TokenKind::While),
+        ])
+    })
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ScannerOptions {
+    // Treats `PRINT` and `print` as the same keyword.
+    pub case_insensitive_keywords: bool,
+    // Checked before the default `keywords()`, so an entry may also rebind one.
+    pub extra_keywords: HashMap<&'static str, TokenKind>,
 }
 
 pub struct Scanner<'a, R>
@@ -37,102 +60,198 @@ where
     R: Reporter,
 {
     pub source: &'a str,
-    pub tokens: Vec<Token<'a>>,
+    // The token scanned by the last `parse_token`, if it made one.
+    token: Option<Token>,
     reporter: &'a mut R,
-    source_bytes: &'a [u8],
     start: usize,
     pointer: usize,
     line: u32,
-    keywords: HashMap<&'a str, TokenKind>,
+    column: u32,
+    // Where the current token began, so multi-line tokens point at their start.
+    start_line: u32,
+    start_column: u32,
+    case_insensitive_keywords: bool,
+    // `ScannerOptions::extra_keywords`, lowercased when keywords are
+    // case-insensitive so they match the lowercased text.
+    extra_keywords: HashMap<String, TokenKind>,
+    emitted_eof: bool,
+}
+
+impl Scanner<'_, Vec<Diagnostic>> {
+    // Scans without a reporter, handing back the errors alongside the tokens.
+    pub fn scan_collecting_errors(source: &str) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut errors = vec![];
+        let tokens = Scanner::new(&mut errors, source).scan();
+
+        (tokens, errors)
+    }
 }
 
 impl<'a, R> Scanner<'a, R>
 where
     R: Reporter,
 {
-    pub fn scan(reporter: &'a mut R, source: &'a str) -> Vec<Token<'a>> {
-        let mut scanner = Self {
+    pub fn new(reporter: &'a mut R, source: &'a str) -> Self {
+        Self::with_options(reporter, source, ScannerOptions::default())
+    }
+
+    pub fn with_options(reporter: &'a mut R, source: &'a str, options: ScannerOptions) -> Self {
+        let case_insensitive_keywords = options.case_insensitive_keywords;
+        let extra_keywords = options
+            .extra_keywords
+            .into_iter()
+            .map(|(text, kind)| {
+                let text = if case_insensitive_keywords {
+                    text.to_lowercase()
+                } else {
+                    text.to_string()
+                };
+                (text, kind)
+            })
+            .collect();
+
+        Self {
             source,
-            source_bytes: source.as_bytes(),
             reporter,
-            keywords: keywords(),
+            case_insensitive_keywords,
+            extra_keywords,
             start: 0,
             pointer: 0,
-            line: 0,
-            tokens: vec![],
-        };
+            line: 1,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
+            token: None,
+            emitted_eof: false,
+        }
+    }
+
+    // Scans whatever is left, so it also finishes a partly streamed source.
+    // Takes `self` because `Iterator::scan` would shadow a `&mut self`
+    // method; use `by_ref().collect()` to keep the scanner.
+    pub fn scan(self) -> Vec<Token> {
+        self.collect()
+    }
+
+    // Yields tokens one at a time, ending with a single `Eof`.
+    pub fn next_token(&mut self) -> Option<Token> {
+        while self.token.is_none() {
+            self.start = self.pointer;
+            self.start_line = self.line;
+            self.start_column = self.column;
 
-        while !scanner.done() {
-            scanner.start = scanner.pointer;
-            scanner.parse_token();
+            if !self.done() {
+                self.parse_token();
+            } else if !self.emitted_eof {
+                self.emitted_eof = true;
+                self.add_token(TokenKind::Eof);
+            } else {
+                return None;
+            }
         }
 
-        scanner.start = scanner.pointer;
-        scanner.add_token(TokenKind::EOF);
-        scanner.tokens
+        self.token.take()
     }
 
     fn parse_token(&mut self) {
         let char = self.consume();
         match char {
-            b' ' | b'\t' | b'\r' => (),
-            b'\n' => {
-                self.line += 1;
-            }
+            ' ' | '\t' | '\r' => (),
+            '\n' => self.newline(),
             // Single-character tokens.
-            b'(' => self.add_token(TokenKind::LeftParen),
-            b')' => self.add_token(TokenKind::RightParen),
-            b'{' => self.add_token(TokenKind::LeftBrace),
-            b'}' => self.add_token(TokenKind::RightBrace),
-            b',' => self.add_token(TokenKind::Comma),
-            b'.' => self.add_token(TokenKind::Dot),
-            b'-' => self.add_token(TokenKind::Minus),
-            b'+' => self.add_token(TokenKind::Plus),
-            b';' => self.add_token(TokenKind::Semicolon),
-            b'*' => self.add_token(TokenKind::Star),
-            b'/' => {
+            '(' => self.add_token(TokenKind::LeftParen),
+            ')' => self.add_token(TokenKind::RightParen),
+            '{' => self.add_token(TokenKind::LeftBrace),
+            '}' => self.add_token(TokenKind::RightBrace),
+            '[' => self.add_token(TokenKind::LeftBracket),
+            ']' => self.add_token(TokenKind::RightBracket),
+            ',' => self.add_token(TokenKind::Comma),
+            '.' => self.add_token(TokenKind::Dot),
+            ':' => self.add_token(TokenKind::Colon),
+            '?' => self.add_token(TokenKind::Question),
+            ';' => self.add_token(TokenKind::Semicolon),
+            '%' => self.add_token(TokenKind::Percent),
+            '&' => self.add_token(TokenKind::Ampersand),
+            '|' => self.add_token(TokenKind::Pipe),
+            '^' => self.add_token(TokenKind::Caret),
+            '~' => self.add_token(TokenKind::Tilde),
+            '/' => {
                 // Maybe comment
-                if self.char_eq(&b'/') {
+                if self.char_eq('/') {
                     self.comment()
+                } else if self.consume_eq('*') {
+                    self.block_comment()
+                } else if self.consume_eq('=') {
+                    self.add_token(TokenKind::SlashEqual)
                 } else {
                     self.add_token(TokenKind::Slash)
                 }
             }
             // One or two character tokens.
-            b'!' => {
-                if self.consume_eq(&b'=') {
+            '-' => {
+                if self.consume_eq('=') {
+                    self.add_token(TokenKind::MinusEqual)
+                } else if self.consume_eq('-') {
+                    self.add_token(TokenKind::MinusMinus)
+                } else {
+                    self.add_token(TokenKind::Minus)
+                }
+            }
+            '+' => {
+                if self.consume_eq('=') {
+                    self.add_token(TokenKind::PlusEqual)
+                } else if self.consume_eq('+') {
+                    self.add_token(TokenKind::PlusPlus)
+                } else {
+                    self.add_token(TokenKind::Plus)
+                }
+            }
+            '*' => {
+                if self.consume_eq('=') {
+                    self.add_token(TokenKind::StarEqual)
+                } else {
+                    self.add_token(TokenKind::Star)
+                }
+            }
+            '!' => {
+                if self.consume_eq('=') {
                     self.add_token(TokenKind::BangEqual)
                 } else {
                     self.add_token(TokenKind::Bang)
                 }
             }
-            b'=' => {
-                if self.consume_eq(&b'=') {
+            '=' => {
+                if self.consume_eq('=') {
                     self.add_token(TokenKind::EqualEqual)
                 } else {
                     self.add_token(TokenKind::Equal)
                 }
             }
-            b'>' => {
-                if self.consume_eq(&b'=') {
+            '>' => {
+                if self.consume_eq('=') {
                     self.add_token(TokenKind::GreaterEqual)
+                } else if self.consume_eq('>') {
+                    self.add_token(TokenKind::GreaterGreater)
                 } else {
                     self.add_token(TokenKind::Greater)
                 }
             }
-            b'<' => {
-                if self.consume_eq(&b'=') {
+            '<' => {
+                if self.consume_eq('=') {
                     self.add_token(TokenKind::LessEqual)
+                } else if self.consume_eq('<') {
+                    self.add_token(TokenKind::LessLess)
                 } else {
                     self.add_token(TokenKind::Less)
                 }
             }
-            b'"' => self.string(),
-            b'0'..=b'9' => self.number(),
-            b'_' | b'a'..=b'z' | b'A'..=b'Z' => self.literal(),
+            '"' => self.string(),
+            '\'' => self.char_literal(),
+            '0'..='9' => self.number(),
+            _ if is_alpha(char) => self.literal(),
             _ => {
                 let msg = format!("Unexpected character '{char}'.");
-                self.reporter.error(self.line, &msg);
+                self.error(&msg);
             }
         }
     }
@@ -142,61 +261,252 @@ where
             self.step();
         }
 
-        self.add_token(
-            *self
-                .keywords
-                .get(&self.source[self.start..self.pointer])
-                .unwrap_or(&TokenKind::Identifier),
-        )
+        let text = &self.source[self.start..self.pointer];
+        let kind = if self.case_insensitive_keywords {
+            self.keyword(&text.to_lowercase())
+        } else {
+            self.keyword(text)
+        };
+
+        match kind {
+            TokenKind::True => self.add_literal_token(kind, Literal::Bool(true)),
+            TokenKind::False => self.add_literal_token(kind, Literal::Bool(false)),
+            TokenKind::Nil => self.add_literal_token(kind, Literal::Nil),
+            _ => self.add_token(kind),
+        }
+    }
+
+    fn keyword(&self, text: &str) -> TokenKind {
+        let extra = self.extra_keywords.get(text);
+        *extra
+            .or_else(|| keywords().get(text))
+            .unwrap_or(&TokenKind::Identifier)
     }
 
     fn number(&mut self) {
-        while self.is_digit() {
+        if self.source[self.start..].starts_with('0') {
+            if self.consume_eq('x') || self.consume_eq('X') {
+                self.radix_number(16, "Invalid hexadecimal literal.");
+                return;
+            }
+            if self.consume_eq('b') || self.consume_eq('B') {
+                self.radix_number(2, "Invalid binary literal.");
+                return;
+            }
+        }
+
+        let mut valid = self.digits();
+
+        if self.char_eq('.') && self.next_is_digit() {
             self.step();
+            valid &= self.digits();
+        }
+
+        if self.consume_eq('e') || self.consume_eq('E') {
+            if !self.consume_eq('+') {
+                self.consume_eq('-');
+            }
+            if !self.is_digit() {
+                self.error("Missing exponent digits in number literal.");
+                return;
+            }
+            valid &= self.digits();
         }
 
-        if self.char_eq(&b'.') && self.next_is_digit() {
+        if !valid {
+            self.error("Invalid digit separator in number literal.");
+            return;
+        }
+
+        let value = self.source[self.start..self.pointer]
+            .replace('_', "")
+            .parse()
+            .unwrap();
+        self.add_literal_token(TokenKind::Number, Literal::Number(value));
+    }
+
+    // Underscores are allowed only between two digits.
+    fn digits(&mut self) -> bool {
+        let mut valid = true;
+        while self.is_digit() || self.char_eq('_') {
+            if self.char_eq('_') && !self.next_is_digit() {
+                valid = false;
+            }
             self.step();
+        }
+
+        valid
+    }
+
+    fn radix_number(&mut self, radix: u32, error: &str) {
+        let digits_start = self.pointer;
+        while self.is_alphanumeric() {
             self.step();
-            while self.is_digit() {
-                self.step();
-            }
         }
 
-        self.add_token(TokenKind::Number);
+        match u64::from_str_radix(&self.source[digits_start..self.pointer], radix) {
+            Ok(value) => self.add_literal_token(TokenKind::Number, Literal::Number(value as f64)),
+            Err(_) => self.error(error),
+        }
     }
 
     fn string(&mut self) {
-        while !self.done() && !self.char_eq(&b'"') {
-            if self.char_eq(&b'\n') {
-                self.line += 1;
+        let mut value = String::new();
+        while !self.done() && !self.char_eq('"') {
+            let char = self.consume();
+            match char {
+                '\\' => self.escape(&mut value),
+                '\n' => {
+                    self.newline();
+                    value.push(char);
+                }
+                _ => value.push(char),
             }
-            self.step();
         }
 
         if self.done() {
-            self.reporter.error(self.line, "Unterminated string.");
+            // Point at the opening quote rather than the end of input.
+            self.reporter
+                .error(self.start_line, self.start_column, "Unterminated string.");
         } else {
             self.step();
-            self.add_token(TokenKind::String);
+            self.add_literal_token(TokenKind::String, Literal::Str(value));
+        }
+    }
+
+    fn char_literal(&mut self) {
+        let mut value = String::new();
+        while !self.done() && !self.char_eq('\'') && !self.char_eq('\n') {
+            let char = self.consume();
+            match char {
+                '\\' => self.escape(&mut value),
+                _ => value.push(char),
+            }
+        }
+
+        if !self.consume_eq('\'') {
+            self.error("Unterminated character literal.");
+            return;
+        }
+
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(char), None) => self.add_literal_token(TokenKind::Char, Literal::Char(char)),
+            // An invalid escape has already been reported.
+            (None, _) if self.pointer - self.start > 2 => (),
+            (None, _) => self.error("Empty character literal."),
+            (Some(_), Some(_)) => self.error("Character literal must be a single character."),
+        }
+    }
+
+    fn escape(&mut self, value: &mut String) {
+        // A trailing backslash is reported as an unterminated string.
+        if self.done() {
+            return;
+        }
+
+        let char = self.consume();
+        let decoded = match char {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '"' => '"',
+            '\'' => '\'',
+            '\\' => '\\',
+            'u' => return self.unicode_escape(value),
+            _ => {
+                if char == '\n' {
+                    self.newline();
+                }
+                self.error("Invalid escape sequence.");
+                return;
+            }
+        };
+        value.push(decoded);
+    }
+
+    fn unicode_escape(&mut self, value: &mut String) {
+        if !self.consume_eq('{') {
+            self.error("Invalid unicode escape.");
+            return;
+        }
+
+        let digits_start = self.pointer;
+        while self.char().is_some_and(|char| char.is_ascii_hexdigit()) {
+            self.step();
+        }
+        let digits = &self.source[digits_start..self.pointer];
+
+        if !self.consume_eq('}') || digits.is_empty() || digits.len() > 6 {
+            self.error("Invalid unicode escape.");
+            return;
+        }
+
+        match u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(char) => value.push(char),
+            None => self.error("Invalid unicode escape."),
         }
     }
 
     fn comment(&mut self) {
-        while !self.done() && !self.char_eq(&b'\n') {
+        while !self.done() && !self.char_eq('\n') {
+            self.step();
+        }
+    }
+
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while !self.done() {
+            if self.char_eq('/') && self.next_char() == Some('*') {
+                self.step();
+                self.step();
+                depth += 1;
+                continue;
+            }
+            if self.char_eq('*') && self.next_char() == Some('/') {
+                self.step();
+                self.step();
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+                continue;
+            }
+            if self.char_eq('\n') {
+                self.step();
+                self.newline();
+                continue;
+            }
             self.step();
         }
+
+        self.error("Unterminated block comment.");
     }
 
     fn add_token(&mut self, kind: TokenKind) {
-        self.tokens.push(Token {
+        self.push_token(kind, None);
+    }
+
+    fn add_literal_token(&mut self, kind: TokenKind, literal: Literal) {
+        self.push_token(kind, Some(literal));
+    }
+
+    fn push_token(&mut self, kind: TokenKind, literal: Option<Literal>) {
+        self.token = Some(Token {
             kind,
-            line: self.line,
-            lexeme: &self.source[self.start..self.pointer],
+            line: self.start_line,
+            column: self.start_column,
+            start: self.start,
+            end: self.pointer,
+            lexeme: self.source[self.start..self.pointer].to_string(),
+            literal,
         })
     }
 
-    fn consume_eq(&mut self, char: &u8) -> bool {
+    fn consume_eq(&mut self, char: char) -> bool {
         if self.char_eq(char) {
             self.step();
             true
@@ -205,62 +515,111 @@ where
         }
     }
 
-    fn consume(&mut self) -> &u8 {
-        let char = &self.source_bytes[self.pointer];
+    fn consume(&mut self) -> char {
+        let char = self.char().unwrap();
         self.step();
         char
     }
 
     fn is_digit(&self) -> bool {
-        self.char().map_or(false, is_digit)
+        self.char().is_some_and(is_digit)
     }
 
     fn next_is_digit(&self) -> bool {
-        self.next_char().map_or(false, is_digit)
+        self.next_char().is_some_and(is_digit)
     }
 
     fn is_alphanumeric(&self) -> bool {
-        self.char().map_or(false, is_alphanumeric)
+        self.char().is_some_and(is_alphanumeric)
+    }
+
+    fn char_eq(&self, char: char) -> bool {
+        self.char() == Some(char)
     }
 
-    fn char_eq(&self, char: &u8) -> bool {
-        self.char().map_or(false, |current| current == char)
+    fn char(&self) -> Option<char> {
+        self.source[self.pointer..].chars().next()
     }
 
-    fn char(&self) -> Option<&u8> {
-        self.source_bytes.get(self.pointer)
+    fn next_char(&self) -> Option<char> {
+        self.source[self.pointer..].chars().nth(1)
     }
 
-    fn next_char(&self) -> Option<&u8> {
-        self.source_bytes.get(self.pointer + 1)
+    fn error(&mut self, msg: &str) {
+        // Point at the most recently consumed character.
+        let column = self.column.saturating_sub(1).max(1);
+        self.reporter.error(self.line, column, msg);
+    }
+
+    fn newline(&mut self) {
+        self.line += 1;
+        self.column = 1;
     }
 
     fn step(&mut self) {
-        self.pointer += 1;
+        if let Some(char) = self.char() {
+            self.pointer += char.len_utf8();
+            self.column += 1;
+        }
     }
 
     fn done(&self) -> bool {
-        self.pointer >= self.source_bytes.len()
+        self.pointer >= self.source.len()
+    }
+}
+
+impl<R> Iterator for Scanner<'_, R>
+where
+    R: Reporter,
+{
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.next_token()
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::lox::token::{Token, TokenKind};
     use crate::lox::Lox;
+    use crate::lox::diagnostic::Severity;
+    use crate::lox::token::{Token, TokenKind};
+
+    struct TestReporter {
+        errors: Vec<String>,
+    }
+
+    impl Reporter for TestReporter {
+        fn report(&mut self, line: u32, column: u32, info: &str, msg: &str) {
+            self.errors
+                .push(format!("[line {line}, column {column}] Error{info}: {msg}"));
+        }
+
+        fn report_runtime(&mut self, line: u32, msg: &str) {
+            self.errors.push(format!("{msg}\n[line {line}]"));
+        }
+        fn warning(&mut self, line: u32, column: u32, msg: &str) {
+            self.errors
+                .push(format!("[line {line}, column {column}] Warning: {msg}"));
+        }
+    }
 
     #[test]
     fn empty_source() {
-        let mut lox = Lox { has_error: false };
-        let tokens = Scanner::scan(&mut lox, "");
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "").scan();
 
         assert_eq!(
             tokens,
             vec![Token {
-                kind: TokenKind::EOF,
-                line: 0,
-                lexeme: ""
+                kind: TokenKind::Eof,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 0,
+                lexeme: "".to_string(),
+                literal: None,
             }]
         );
     }
@@ -272,14 +631,22 @@ mod test {
             (")", TokenKind::RightParen),
             ("{", TokenKind::LeftBrace),
             ("}", TokenKind::RightBrace),
+            ("[", TokenKind::LeftBracket),
+            ("]", TokenKind::RightBracket),
             (",", TokenKind::Comma),
             (".", TokenKind::Dot),
+            (":", TokenKind::Colon),
+            ("?", TokenKind::Question),
             ("-", TokenKind::Minus),
             ("+", TokenKind::Plus),
             (";", TokenKind::Semicolon),
             ("/", TokenKind::Slash),
             ("*", TokenKind::Star),
-            ("*", TokenKind::Star),
+            ("%", TokenKind::Percent),
+            ("&", TokenKind::Ampersand),
+            ("|", TokenKind::Pipe),
+            ("^", TokenKind::Caret),
+            ("~", TokenKind::Tilde),
             ("!", TokenKind::Bang),
             ("!=", TokenKind::BangEqual),
             ("=", TokenKind::Equal),
@@ -288,63 +655,860 @@ mod test {
             (">=", TokenKind::GreaterEqual),
             ("<", TokenKind::Less),
             ("<=", TokenKind::LessEqual),
-            ("\"string\"", TokenKind::String),
-            ("123", TokenKind::Number),
-            ("3.14", TokenKind::Number),
+            ("<<", TokenKind::LessLess),
+            (">>", TokenKind::GreaterGreater),
+            ("-=", TokenKind::MinusEqual),
+            ("+=", TokenKind::PlusEqual),
+            ("--", TokenKind::MinusMinus),
+            ("++", TokenKind::PlusPlus),
+            ("/=", TokenKind::SlashEqual),
+            ("*=", TokenKind::StarEqual),
             ("and", TokenKind::And),
+            ("break", TokenKind::Break),
+            ("case", TokenKind::Case),
             ("class", TokenKind::Class),
+            ("const", TokenKind::Const),
+            ("continue", TokenKind::Continue),
+            ("default", TokenKind::Default),
             ("else", TokenKind::Else),
-            ("false", TokenKind::False),
             ("fun", TokenKind::Fun),
             ("for", TokenKind::For),
             ("if", TokenKind::If),
-            ("nil", TokenKind::Nil),
             ("or", TokenKind::Or),
             ("print", TokenKind::Print),
             ("return", TokenKind::Return),
             ("super", TokenKind::Super),
+            ("switch", TokenKind::Switch),
             ("this", TokenKind::This),
-            ("true", TokenKind::True),
             ("var", TokenKind::Var),
             ("while", TokenKind::While),
             ("identifier", TokenKind::Identifier),
         ];
 
         for (code, kind) in variants {
-            let mut lox = Lox { has_error: false };
-            let tokens = Scanner::scan(&mut lox, code);
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
 
             assert_eq!(
                 tokens,
                 vec![
                     Token {
                         kind,
-                        line: 0,
-                        lexeme: code,
+                        line: 1,
+                        column: 1,
+                        start: 0,
+                        end: code.len(),
+                        lexeme: code.to_string(),
+                        literal: None,
                     },
                     Token {
-                        kind: TokenKind::EOF,
-                        line: 0,
-                        lexeme: "",
+                        kind: TokenKind::Eof,
+                        line: 1,
+                        column: code.len() as u32 + 1,
+                        start: code.len(),
+                        end: code.len(),
+                        lexeme: "".to_string(),
+                        literal: None,
                     }
                 ],
             );
-            assert_eq!(lox.has_error, false);
+            assert!(!lox.has_error);
         }
     }
 
     #[test]
     fn comment_only() {
-        let mut lox = Lox { has_error: false };
-        let tokens = Scanner::scan(&mut lox, "// comment text");
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "// comment text").scan();
 
         assert_eq!(
             tokens,
             vec![Token {
-                kind: TokenKind::EOF,
-                line: 0,
-                lexeme: ""
+                kind: TokenKind::Eof,
+                line: 1,
+                column: 16,
+                start: 15,
+                end: 15,
+                lexeme: "".to_string(),
+                literal: None,
             }]
         );
     }
+
+    #[test]
+    fn multiline_source() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "var\nname\n\n;").scan();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    kind: TokenKind::Var,
+                    line: 1,
+                    column: 1,
+                    start: 0,
+                    end: 3,
+                    lexeme: "var".to_string(),
+                    literal: None,
+                },
+                Token {
+                    kind: TokenKind::Identifier,
+                    line: 2,
+                    column: 1,
+                    start: 4,
+                    end: 8,
+                    lexeme: "name".to_string(),
+                    literal: None,
+                },
+                Token {
+                    kind: TokenKind::Semicolon,
+                    line: 4,
+                    column: 1,
+                    start: 10,
+                    end: 11,
+                    lexeme: ";".to_string(),
+                    literal: None,
+                },
+                Token {
+                    kind: TokenKind::Eof,
+                    line: 4,
+                    column: 2,
+                    start: 11,
+                    end: 11,
+                    lexeme: "".to_string(),
+                    literal: None,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn unexpected_character() {
+        let mut reporter = TestReporter { errors: vec![] };
+        Scanner::new(&mut reporter, "@").scan();
+
+        assert_eq!(
+            reporter.errors,
+            vec!["[line 1, column 1] Error: Unexpected character '@'."]
+        );
+    }
+
+    #[test]
+    fn block_comment() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "/* comment */;").scan();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    kind: TokenKind::Semicolon,
+                    line: 1,
+                    column: 14,
+                    start: 13,
+                    end: 14,
+                    lexeme: ";".to_string(),
+                    literal: None,
+                },
+                Token {
+                    kind: TokenKind::Eof,
+                    line: 1,
+                    column: 15,
+                    start: 14,
+                    end: 14,
+                    lexeme: "".to_string(),
+                    literal: None,
+                }
+            ]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn multiline_block_comment() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "/* first\nsecond\nthird */;").scan();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    kind: TokenKind::Semicolon,
+                    line: 3,
+                    column: 9,
+                    start: 24,
+                    end: 25,
+                    lexeme: ";".to_string(),
+                    literal: None,
+                },
+                Token {
+                    kind: TokenKind::Eof,
+                    line: 3,
+                    column: 10,
+                    start: 25,
+                    end: 25,
+                    lexeme: "".to_string(),
+                    literal: None,
+                }
+            ]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let mut reporter = TestReporter { errors: vec![] };
+        Scanner::new(&mut reporter, "/* comment\n").scan();
+
+        assert_eq!(
+            reporter.errors,
+            vec!["[line 2, column 1] Error: Unterminated block comment."]
+        );
+    }
+
+    #[test]
+    fn nested_block_comment() {
+        let sources = [
+            "/* outer /* inner */ still outer */;",
+            "/* one /* two /* three */ two */ one */;",
+        ];
+
+        for code in sources {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(
+                tokens,
+                vec![
+                    Token {
+                        kind: TokenKind::Semicolon,
+                        line: 1,
+                        column: code.len() as u32,
+                        start: code.len() - 1,
+                        end: code.len(),
+                        lexeme: ";".to_string(),
+                        literal: None,
+                    },
+                    Token {
+                        kind: TokenKind::Eof,
+                        line: 1,
+                        column: code.len() as u32 + 1,
+                        start: code.len(),
+                        end: code.len(),
+                        lexeme: "".to_string(),
+                        literal: None,
+                    }
+                ]
+            );
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn unbalanced_nested_block_comment() {
+        let mut reporter = TestReporter { errors: vec![] };
+        Scanner::new(&mut reporter, "/* outer /* inner */ still outer;").scan();
+
+        assert_eq!(
+            reporter.errors,
+            vec!["[line 1, column 33] Error: Unterminated block comment."]
+        );
+    }
+
+    #[test]
+    fn string_literal() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "\"string\"").scan();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    kind: TokenKind::String,
+                    line: 1,
+                    column: 1,
+                    start: 0,
+                    end: 8,
+                    lexeme: "\"string\"".to_string(),
+                    literal: Some(Literal::Str("string".to_string())),
+                },
+                Token {
+                    kind: TokenKind::Eof,
+                    line: 1,
+                    column: 9,
+                    start: 8,
+                    end: 8,
+                    lexeme: "".to_string(),
+                    literal: None,
+                }
+            ]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn string_escapes() {
+        let variants = [
+            (r#""a\tb""#, "a\tb"),
+            (r#""a\nb""#, "a\nb"),
+            (r#""a\rb""#, "a\rb"),
+            (r#""say \"hi\"""#, "say \"hi\""),
+            (r#""back\\slash""#, "back\\slash"),
+        ];
+
+        for (code, value) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(tokens[0].kind, TokenKind::String);
+            assert_eq!(tokens[0].lexeme, code);
+            assert_eq!(tokens[0].literal, Some(Literal::Str(value.to_string())));
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn invalid_escape() {
+        let mut reporter = TestReporter { errors: vec![] };
+        Scanner::new(&mut reporter, r#""\q""#).scan();
+
+        assert_eq!(
+            reporter.errors,
+            vec!["[line 1, column 3] Error: Invalid escape sequence."]
+        );
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        let variants = [
+            (r#""\u{e9}""#, "\u{e9}"),
+            (r#""\u{20AC} sign""#, "\u{20AC} sign"),
+            (r#""\u{1F600}""#, "\u{1F600}"),
+        ];
+
+        for (code, value) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(tokens[0].literal, Some(Literal::Str(value.to_string())));
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn invalid_unicode_escape() {
+        let variants = [
+            (r#""\u1F600""#, 3),
+            (r#""\u{1F6G0}""#, 7),
+            (r#""\u{}""#, 5),
+            (r#""\u{1234567}""#, 12),
+            (r#""\u{110000}""#, 11),
+            (r#""\u{D800}""#, 9),
+        ];
+
+        for (code, column) in variants {
+            let mut reporter = TestReporter { errors: vec![] };
+            Scanner::new(&mut reporter, code).scan();
+
+            assert_eq!(
+                reporter.errors,
+                vec![format!(
+                    "[line 1, column {column}] Error: Invalid unicode escape."
+                )],
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn literal_tokens() {
+        let variants = [
+            ("123", TokenKind::Number, Literal::Number(123.0)),
+            ("3.14", TokenKind::Number, Literal::Number(3.14)),
+            ("true", TokenKind::True, Literal::Bool(true)),
+            ("false", TokenKind::False, Literal::Bool(false)),
+            ("nil", TokenKind::Nil, Literal::Nil),
+        ];
+
+        for (code, kind, literal) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(
+                tokens,
+                vec![
+                    Token {
+                        kind,
+                        line: 1,
+                        column: 1,
+                        start: 0,
+                        end: code.len(),
+                        lexeme: code.to_string(),
+                        literal: Some(literal),
+                    },
+                    Token {
+                        kind: TokenKind::Eof,
+                        line: 1,
+                        column: code.len() as u32 + 1,
+                        start: code.len(),
+                        end: code.len(),
+                        lexeme: "".to_string(),
+                        literal: None,
+                    }
+                ],
+            );
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn hexadecimal_literals() {
+        let variants = [("0xFF", 255.0), ("0x0", 0.0), ("0Xab", 171.0)];
+
+        for (code, value) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(tokens[0].kind, TokenKind::Number);
+            assert_eq!(tokens[0].lexeme, code);
+            assert_eq!(tokens[0].literal, Some(Literal::Number(value)));
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn invalid_hexadecimal_literal() {
+        for code in ["0x", "0xFG"] {
+            let mut reporter = TestReporter { errors: vec![] };
+            Scanner::new(&mut reporter, code).scan();
+
+            assert_eq!(
+                reporter.errors,
+                vec![format!(
+                    "[line 1, column {}] Error: Invalid hexadecimal literal.",
+                    code.len()
+                )],
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn binary_literals() {
+        let variants = [("0b1010", 10.0), ("0b0", 0.0), ("0B11", 3.0)];
+
+        for (code, value) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(tokens[0].kind, TokenKind::Number);
+            assert_eq!(tokens[0].lexeme, code);
+            assert_eq!(tokens[0].literal, Some(Literal::Number(value)));
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn invalid_binary_literal() {
+        for code in ["0b", "0b2", "0b102"] {
+            let mut reporter = TestReporter { errors: vec![] };
+            Scanner::new(&mut reporter, code).scan();
+
+            assert_eq!(
+                reporter.errors,
+                vec![format!(
+                    "[line 1, column {}] Error: Invalid binary literal.",
+                    code.len()
+                )],
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn digit_separators() {
+        let variants = [("1_000_000", 1_000_000.0), ("1_000.000_1", 1_000.000_1)];
+
+        for (code, value) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(tokens[0].kind, TokenKind::Number);
+            assert_eq!(tokens[0].lexeme, code);
+            assert_eq!(tokens[0].literal, Some(Literal::Number(value)));
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn invalid_digit_separators() {
+        for code in ["1_", "1__0", "1.5_"] {
+            let mut reporter = TestReporter { errors: vec![] };
+            Scanner::new(&mut reporter, code).scan();
+
+            assert_eq!(
+                reporter.errors,
+                vec![format!(
+                    "[line 1, column {}] Error: Invalid digit separator in number literal.",
+                    code.len()
+                )],
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn exponent_literals() {
+        let variants = [("1e3", 1e3), ("1.5e-2", 1.5e-2), ("2E+4", 2E+4)];
+
+        for (code, value) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(tokens[0].kind, TokenKind::Number);
+            assert_eq!(tokens[0].lexeme, code);
+            assert_eq!(tokens[0].literal, Some(Literal::Number(value)));
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn missing_exponent_digits() {
+        for code in ["1e", "1.5e+"] {
+            let mut reporter = TestReporter { errors: vec![] };
+            Scanner::new(&mut reporter, code).scan();
+
+            assert_eq!(
+                reporter.errors,
+                vec![format!(
+                    "[line 1, column {}] Error: Missing exponent digits in number literal.",
+                    code.len()
+                )],
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn token_columns() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "var  answer\n  = 42;").scan();
+
+        let positions: Vec<(u32, u32)> = tokens
+            .iter()
+            .map(|token| (token.line, token.column))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![(1, 1), (1, 6), (2, 3), (2, 5), (2, 7), (2, 8)]
+        );
+    }
+
+    #[test]
+    fn multibyte_identifiers() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "var café = naïve;").scan();
+
+        let lexemes: Vec<(TokenKind, &str, u32)> = tokens
+            .iter()
+            .map(|token| (token.kind, token.lexeme.as_str(), token.column))
+            .collect();
+        assert_eq!(
+            lexemes,
+            vec![
+                (TokenKind::Var, "var", 1),
+                (TokenKind::Identifier, "café", 5),
+                (TokenKind::Equal, "=", 10),
+                (TokenKind::Identifier, "naïve", 12),
+                (TokenKind::Semicolon, ";", 17),
+                (TokenKind::Eof, "", 18),
+            ]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn multibyte_strings() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "\"é 😀\nsecond\" ;").scan();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    kind: TokenKind::String,
+                    line: 1,
+                    column: 1,
+                    start: 0,
+                    end: 16,
+                    lexeme: "\"é 😀\nsecond\"".to_string(),
+                    literal: Some(Literal::Str("é 😀\nsecond".to_string())),
+                },
+                Token {
+                    kind: TokenKind::Semicolon,
+                    line: 2,
+                    column: 9,
+                    start: 17,
+                    end: 18,
+                    lexeme: ";".to_string(),
+                    literal: None,
+                },
+                Token {
+                    kind: TokenKind::Eof,
+                    line: 2,
+                    column: 10,
+                    start: 18,
+                    end: 18,
+                    lexeme: "".to_string(),
+                    literal: None,
+                }
+            ]
+        );
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn multibyte_unexpected_character() {
+        let mut reporter = TestReporter { errors: vec![] };
+        Scanner::new(&mut reporter, "1 € 2").scan();
+
+        assert_eq!(
+            reporter.errors,
+            vec!["[line 1, column 3] Error: Unexpected character '€'."]
+        );
+    }
+
+    #[test]
+    fn four_byte_character_is_one_error() {
+        let (tokens, errors) = Scanner::scan_collecting_errors("a 🦀 b");
+
+        assert_eq!(
+            errors,
+            vec![Diagnostic {
+                line: 1,
+                column: 3,
+                info: String::new(),
+                msg: "Unexpected character '🦀'.".to_string(),
+                severity: Severity::Error,
+            }]
+        );
+        // Columns count characters, so the emoji only takes up one.
+        assert_eq!(tokens[1].column, 5);
+        assert_eq!(tokens[1].start, 7);
+    }
+
+    #[test]
+    fn scan_after_next_token() {
+        let mut lox = Lox::new();
+        let mut scanner = Scanner::new(&mut lox, "print 1;");
+        let first = scanner.next_token().unwrap();
+        let rest: Vec<TokenKind> = scanner.scan().iter().map(|token| token.kind).collect();
+
+        assert_eq!(first.kind, TokenKind::Print);
+        assert_eq!(
+            rest,
+            vec![TokenKind::Number, TokenKind::Semicolon, TokenKind::Eof]
+        );
+    }
+
+    #[test]
+    fn iterate_tokens() {
+        let code = "var a = \"text\"; // comment\nprint a;";
+        let mut lox = Lox::new();
+        let batch = Scanner::new(&mut lox, code).scan();
+
+        let mut scanner = Scanner::new(&mut lox, code);
+        let first = scanner.next_token().map(|token| token.kind);
+        let streamed: Vec<Token> = scanner.collect();
+
+        assert_eq!(first, Some(TokenKind::Var));
+        assert_eq!(streamed, batch[1..]);
+        assert_eq!(
+            streamed.last().map(|token| token.kind),
+            Some(TokenKind::Eof)
+        );
+    }
+
+    #[test]
+    fn next_token_after_eof() {
+        let mut lox = Lox::new();
+        let mut scanner = Scanner::new(&mut lox, " ");
+
+        assert_eq!(
+            scanner.next_token().map(|token| token.kind),
+            Some(TokenKind::Eof)
+        );
+        assert_eq!(scanner.next_token(), None);
+    }
+
+    #[test]
+    fn token_offsets() {
+        let code = "var é = \"hi\";";
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, code).scan();
+
+        let spans: Vec<_> = tokens
+            .iter()
+            .map(|token| (token.start, token.end, &code[token.start..token.end]))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 3, "var"),
+                (4, 6, "é"),
+                (7, 8, "="),
+                (9, 13, "\"hi\""),
+                (13, 14, ";"),
+                (14, 14, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn char_literal() {
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, "'a'").scan();
+
+        assert_eq!(tokens[0].kind, TokenKind::Char);
+        assert_eq!(tokens[0].lexeme, "'a'");
+        assert_eq!(tokens[0].literal, Some(Literal::Char('a')));
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn escaped_char_literals() {
+        let variants = [(r"'\n'", '\n'), (r"'\''", '\''), (r"'\u{e9}'", 'é')];
+
+        for (code, value) in variants {
+            let mut lox = Lox::new();
+            let tokens = Scanner::new(&mut lox, code).scan();
+
+            assert_eq!(tokens[0].literal, Some(Literal::Char(value)), "{code}");
+            assert!(!lox.has_error);
+        }
+    }
+
+    #[test]
+    fn invalid_char_literals() {
+        let variants = [
+            (
+                "'ab'",
+                "[line 1, column 4] Error: Character literal must be a single character.",
+            ),
+            ("''", "[line 1, column 2] Error: Empty character literal."),
+            (
+                "'a",
+                "[line 1, column 2] Error: Unterminated character literal.",
+            ),
+            (
+                r"'\q'",
+                "[line 1, column 3] Error: Invalid escape sequence.",
+            ),
+        ];
+
+        for (code, error) in variants {
+            let mut reporter = TestReporter { errors: vec![] };
+            Scanner::new(&mut reporter, code).scan();
+
+            assert_eq!(reporter.errors, vec![error], "{code}");
+        }
+    }
+
+    #[test]
+    fn unterminated_multiline_string() {
+        let mut reporter = TestReporter { errors: vec![] };
+        Scanner::new(&mut reporter, "print \"open\nstill open\n").scan();
+
+        assert_eq!(
+            reporter.errors,
+            vec!["[line 1, column 7] Error: Unterminated string."]
+        );
+    }
+
+    #[test]
+    fn case_insensitive_keywords() {
+        let mut lox = Lox::new();
+        let options = ScannerOptions {
+            case_insensitive_keywords: true,
+            ..ScannerOptions::default()
+        };
+        let insensitive = Scanner::with_options(&mut lox, "PRINT True", options).scan();
+        let sensitive = Scanner::new(&mut lox, "PRINT True").scan();
+
+        assert_eq!(insensitive[0].kind, TokenKind::Print);
+        assert_eq!(insensitive[1].literal, Some(Literal::Bool(true)));
+        assert_eq!(sensitive[0].kind, TokenKind::Identifier);
+        assert_eq!(sensitive[1].kind, TokenKind::Identifier);
+    }
+
+    #[test]
+    fn extra_keywords() {
+        let mut lox = Lox::new();
+        let options = ScannerOptions {
+            extra_keywords: HashMap::from([("function", TokenKind::Fun)]),
+            ..ScannerOptions::default()
+        };
+        let tokens = Scanner::with_options(&mut lox, "function fun", options).scan();
+
+        assert_eq!(tokens[0].kind, TokenKind::Fun);
+        assert_eq!(tokens[0].lexeme, "function");
+        assert_eq!(tokens[1].kind, TokenKind::Fun);
+    }
+
+    #[test]
+    fn case_insensitive_extra_keywords() {
+        let mut lox = Lox::new();
+        let options = ScannerOptions {
+            case_insensitive_keywords: true,
+            extra_keywords: HashMap::from([("Function", TokenKind::Fun)]),
+        };
+        let tokens = Scanner::with_options(&mut lox, "FUNCTION function", options).scan();
+
+        assert_eq!(tokens[0].kind, TokenKind::Fun);
+        assert_eq!(tokens[1].kind, TokenKind::Fun);
+    }
+
+    #[test]
+    fn scan_many_lines() {
+        let code = "var a = 1; while (a < 10) { print a; a = a + 1; }\n".repeat(10_000);
+        let mut lox = Lox::new();
+        let tokens = Scanner::new(&mut lox, &code).scan();
+
+        assert_eq!(tokens.len(), 22 * 10_000 + 1);
+        assert_eq!(tokens.last().unwrap().line, 10_001);
+        assert!(std::ptr::eq(keywords(), keywords()));
+        assert!(!lox.has_error);
+    }
+
+    #[test]
+    fn scan_collecting_errors_without_reporter() {
+        let (tokens, errors) = Scanner::scan_collecting_errors("a @ 1\n\"open");
+
+        let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![TokenKind::Identifier, TokenKind::Number, TokenKind::Eof]
+        );
+        assert_eq!(
+            errors,
+            vec![
+                Diagnostic {
+                    line: 1,
+                    column: 3,
+                    info: String::new(),
+                    msg: "Unexpected character '@'.".to_string(),
+                    severity: Severity::Error,
+                },
+                Diagnostic {
+                    line: 2,
+                    column: 1,
+                    info: String::new(),
+                    msg: "Unterminated string.".to_string(),
+                    severity: Severity::Error,
+                },
+            ]
+        );
+    }
 }
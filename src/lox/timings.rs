@@ -0,0 +1,51 @@
+use std::fmt;
+use std::time::Duration;
+
+// Time spent in each phase of a run, zero for phases skipped after an error.
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub struct Timings {
+    pub scan: Duration,
+    pub parse: Duration,
+    pub resolve: Duration,
+    pub interpret: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.scan + self.parse + self.resolve + self.interpret
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "scan:      {:?}", self.scan)?;
+        writeln!(f, "parse:     {:?}", self.parse)?;
+        writeln!(f, "resolve:   {:?}", self.resolve)?;
+        writeln!(f, "interpret: {:?}", self.interpret)?;
+        write!(f, "total:     {:?}", self.total())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display_timings() {
+        let timings = Timings {
+            scan: Duration::from_micros(5),
+            parse: Duration::from_micros(10),
+            resolve: Duration::from_micros(1),
+            interpret: Duration::from_millis(2),
+        };
+
+        assert_eq!(
+            timings.to_string(),
+            "scan:      5µs\n\
+             parse:     10µs\n\
+             resolve:   1µs\n\
+             interpret: 2ms\n\
+             total:     2.016ms"
+        );
+    }
+}
@@ -1,17 +1,29 @@
+use std::fmt;
+
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenKind {
     // Single-character tokens.
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
+    Colon,
+    Question,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
 
     // One or two character tokens.
     Bang,
@@ -22,15 +34,29 @@ pub enum TokenKind {
     GreaterEqual,
     Less,
     LessEqual,
+    LessLess,
+    GreaterGreater,
+    MinusEqual,
+    MinusMinus,
+    PlusEqual,
+    PlusPlus,
+    SlashEqual,
+    StarEqual,
 
     // Literals.
     Identifier,
     String,
+    Char,
     Number,
 
     // Keywords.
     And,
+    Break,
+    Case,
     Class,
+    Const,
+    Continue,
+    Default,
     Else,
     False,
     Fun,
@@ -41,17 +67,308 @@ pub enum TokenKind {
     Print,
     Return,
     Super,
+    Switch,
     This,
     True,
     Var,
     While,
 
-    EOF,
+    Eof,
+}
+
+impl TokenKind {
+    // The reserved words, matching the scanner's default keyword table.
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::And
+                | TokenKind::Break
+                | TokenKind::Case
+                | TokenKind::Class
+                | TokenKind::Const
+                | TokenKind::Continue
+                | TokenKind::Default
+                | TokenKind::Else
+                | TokenKind::False
+                | TokenKind::Fun
+                | TokenKind::For
+                | TokenKind::If
+                | TokenKind::Nil
+                | TokenKind::Or
+                | TokenKind::Print
+                | TokenKind::Return
+                | TokenKind::Super
+                | TokenKind::Switch
+                | TokenKind::This
+                | TokenKind::True
+                | TokenKind::Var
+                | TokenKind::While
+        )
+    }
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::LeftBrace => "{",
+            TokenKind::RightBrace => "}",
+            TokenKind::LeftBracket => "[",
+            TokenKind::RightBracket => "]",
+            TokenKind::Comma => ",",
+            TokenKind::Dot => ".",
+            TokenKind::Colon => ":",
+            TokenKind::Question => "?",
+            TokenKind::Minus => "-",
+            TokenKind::Plus => "+",
+            TokenKind::Semicolon => ";",
+            TokenKind::Slash => "/",
+            TokenKind::Star => "*",
+            TokenKind::Percent => "%",
+            TokenKind::Ampersand => "&",
+            TokenKind::Pipe => "|",
+            TokenKind::Caret => "^",
+            TokenKind::Tilde => "~",
+            TokenKind::Bang => "!",
+            TokenKind::BangEqual => "!=",
+            TokenKind::Equal => "=",
+            TokenKind::EqualEqual => "==",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::Less => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::LessLess => "<<",
+            TokenKind::GreaterGreater => ">>",
+            TokenKind::MinusEqual => "-=",
+            TokenKind::MinusMinus => "--",
+            TokenKind::PlusEqual => "+=",
+            TokenKind::PlusPlus => "++",
+            TokenKind::SlashEqual => "/=",
+            TokenKind::StarEqual => "*=",
+            TokenKind::Identifier => "identifier",
+            TokenKind::String => "string",
+            TokenKind::Char => "char",
+            TokenKind::Number => "number",
+            TokenKind::And => "and",
+            TokenKind::Break => "break",
+            TokenKind::Case => "case",
+            TokenKind::Class => "class",
+            TokenKind::Const => "const",
+            TokenKind::Continue => "continue",
+            TokenKind::Default => "default",
+            TokenKind::Else => "else",
+            TokenKind::False => "false",
+            TokenKind::Fun => "fun",
+            TokenKind::For => "for",
+            TokenKind::If => "if",
+            TokenKind::Nil => "nil",
+            TokenKind::Or => "or",
+            TokenKind::Print => "print",
+            TokenKind::Return => "return",
+            TokenKind::Super => "super",
+            TokenKind::Switch => "switch",
+            TokenKind::This => "this",
+            TokenKind::True => "true",
+            TokenKind::Var => "var",
+            TokenKind::While => "while",
+            TokenKind::Eof => "EOF",
+        };
+
+        write!(f, "{text}")
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Literal {
+    Number(f64),
+    Str(String),
+    Char(char),
+    Bool(bool),
+    Nil,
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Number(value) => write!(f, "{value}"),
+            Literal::Str(value) => write!(f, "{value}"),
+            Literal::Char(value) => write!(f, "{value}"),
+            Literal::Bool(value) => write!(f, "{value}"),
+            Literal::Nil => write!(f, "nil"),
+        }
+    }
 }
 
-#[derive(PartialEq, Debug)]
-pub struct Token<'a> {
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Token {
     pub kind: TokenKind,
     pub line: u32,
-    pub lexeme: &'a str,
+    pub column: u32,
+    // Byte offsets of the lexeme in the scanned source.
+    pub start: usize,
+    pub end: usize,
+    pub lexeme: String,
+    pub literal: Option<Literal>,
+}
+
+impl Token {
+    pub fn is_keyword(&self) -> bool {
+        self.kind.is_keyword()
+    }
+
+    // A `{kind, lexeme, line, column}` object, the kind as its variant name.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"kind\": \"{:?}\", \"lexeme\": {}, \"line\": {}, \"column\": {}}}",
+            self.kind,
+            json_string(&self.lexeme),
+            self.line,
+            self.column
+        )
+    }
+}
+
+// Quotes `text` as a JSON string, escaping quotes, backslashes and control
+// characters.
+fn json_string(text: &str) -> String {
+    let mut output = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+
+    output
+}
+
+// Mirrors the book's `Token.toString`: kind, lexeme, then literal or `null`.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} ", self.kind, self.lexeme)?;
+        match &self.literal {
+            Some(literal) => write!(f, "{literal}"),
+            None => write!(f, "null"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display_token_kinds() {
+        let cases = [
+            (TokenKind::LeftParen, "("),
+            (TokenKind::RightBrace, "}"),
+            (TokenKind::BangEqual, "!="),
+            (TokenKind::LessEqual, "<="),
+            (TokenKind::Identifier, "identifier"),
+            (TokenKind::Number, "number"),
+            (TokenKind::Fun, "fun"),
+            (TokenKind::While, "while"),
+            (TokenKind::Eof, "EOF"),
+        ];
+
+        for (kind, text) in cases {
+            assert_eq!(kind.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn keyword_kinds() {
+        for kind in crate::lox::scanner::keywords().values() {
+            assert!(kind.is_keyword(), "{kind:?} is a keyword");
+        }
+
+        let others = [
+            TokenKind::LeftParen,
+            TokenKind::Bang,
+            TokenKind::PlusPlus,
+            TokenKind::Identifier,
+            TokenKind::String,
+            TokenKind::Number,
+            TokenKind::Eof,
+        ];
+        for kind in others {
+            assert!(!kind.is_keyword(), "{kind:?} is not a keyword");
+        }
+    }
+
+    fn token(kind: TokenKind, lexeme: &str, literal: Option<Literal>) -> Token {
+        Token {
+            kind,
+            line: 1,
+            column: 1,
+            start: 0,
+            end: lexeme.len(),
+            lexeme: lexeme.to_string(),
+            literal,
+        }
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn display_tokens() {
+        assert_eq!(
+            token(TokenKind::Number, "3.14", Some(Literal::Number(3.14))).to_string(),
+            "Number 3.14 3.14"
+        );
+        assert_eq!(
+            token(
+                TokenKind::String,
+                "\"hi\"",
+                Some(Literal::Str("hi".to_string()))
+            )
+            .to_string(),
+            "String \"hi\" hi"
+        );
+        assert_eq!(
+            token(TokenKind::LeftParen, "(", None).to_string(),
+            "LeftParen ( null"
+        );
+    }
+
+    #[test]
+    fn token_to_json() {
+        let token = token(
+            TokenKind::String,
+            "\"say \"hi\"\\\n\u{1}\"",
+            Some(Literal::Str(String::new())),
+        );
+
+        assert_eq!(
+            token.to_json(),
+            r#"{"kind": "String", "lexeme": "\"say \"hi\"\\\n\u0001\"", "line": 1, "column": 1}"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let token = token(
+            TokenKind::String,
+            "\"hi\"",
+            Some(Literal::Str("hi".to_string())),
+        );
+        let json = serde_json::to_string(&token).unwrap();
+
+        assert_eq!(serde_json::from_str::<Token>(&json).unwrap(), token);
+    }
+
+    #[test]
+    fn token_is_keyword() {
+        assert!(token(TokenKind::While, "while", None).is_keyword());
+        assert!(!token(TokenKind::Identifier, "whilst", None).is_keyword());
+    }
 }
@@ -0,0 +1,153 @@
+use crate::lox::class::{Class, Instance};
+use crate::lox::function::Function;
+use crate::lox::native::NativeFn;
+use crate::lox::token::Literal;
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    Nil,
+    Function(Rc<Function>),
+    Native {
+        name: &'static str,
+        // Natives may take optional trailing arguments.
+        arity: RangeInclusive<usize>,
+        func: NativeFn,
+    },
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    List(Rc<RefCell<Vec<Value>>>),
+}
+
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    // Names returned by the `type` native, keep them stable.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::Str(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::Nil => "nil",
+            Value::Function(_) | Value::Native { .. } => "function",
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+            Value::List(_) => "list",
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::Str(left), Value::Str(right)) => left == right,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Nil, Value::Nil) => true,
+            (Value::Function(left), Value::Function(right)) => left == right,
+            // Natives are registered once per name.
+            (Value::Native { name: left, .. }, Value::Native { name: right, .. }) => left == right,
+            (Value::Class(left), Value::Class(right)) => Rc::ptr_eq(left, right),
+            (Value::Instance(left), Value::Instance(right)) => Rc::ptr_eq(left, right),
+            (Value::List(left), Value::List(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl From<&Literal> for Value {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::Number(value) => Value::Number(*value),
+            Literal::Str(value) => Value::Str(value.clone()),
+            // Lox has no char type, so chars evaluate to one-character strings.
+            Literal::Char(value) => Value::Str(value.to_string()),
+            Literal::Bool(value) => Value::Bool(*value),
+            Literal::Nil => Value::Nil,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(value) => write!(f, "{}", format_number(*value)),
+            Value::Str(value) => write!(f, "{value}"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Nil => write!(f, "nil"),
+            Value::Function(function) => write!(f, "{function}"),
+            Value::Native { name, .. } => write!(f, "<native fn {name}>"),
+            Value::Class(class) => write!(f, "{class}"),
+            Value::Instance(instance) => write!(f, "{}", instance.borrow()),
+            Value::List(elements) => {
+                let elements: Vec<String> = elements
+                    .borrow()
+                    .iter()
+                    .map(|element| element.to_string())
+                    .collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+        }
+    }
+}
+
+// Whole numbers drop the fraction (`5`, `-0`), huge ones switch to exponent
+// form instead of printing every digit.
+fn format_number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        format!("{sign}Infinity")
+    } else if value.abs() >= 1e21 {
+        format!("{value:e}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn display_numbers() {
+        let cases = [
+            (5.0, "5"),
+            (3.14, "3.14"),
+            (-0.0, "-0"),
+            (-2.5, "-2.5"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e21"),
+            (-1.5e300, "-1.5e300"),
+            (f64::INFINITY, "Infinity"),
+            (f64::NEG_INFINITY, "-Infinity"),
+            (f64::NAN, "NaN"),
+        ];
+
+        for (value, text) in cases {
+            assert_eq!(Value::Number(value).to_string(), text);
+        }
+    }
+
+    #[test]
+    fn equality() {
+        let nan = Value::Number(f64::NAN);
+
+        assert_eq!(Value::Nil, Value::Nil);
+        assert_ne!(Value::Nil, Value::Bool(false));
+        assert_ne!(Value::Number(0.0), Value::Bool(false));
+        assert_ne!(Value::Number(1.0), Value::Str("1".to_string()));
+        assert_eq!(Value::Str("a".to_string()), Value::Str("a".to_string()));
+        assert_ne!(nan, nan);
+    }
+}
@@ -1,6 +1,4 @@
-use crate::lox::Lox;
-
-mod lox;
+use crafting_interpreters::lox::Lox;
 
 fn main() {
     Lox::start();
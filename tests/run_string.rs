@@ -0,0 +1,133 @@
+use crafting_interpreters::lox::{
+    Diagnostic, InterpreterOptions, Lox, Scanner, ScannerOptions, Severity, TokenKind, keywords,
+};
+
+#[test]
+fn run_program() {
+    let source = "fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); }\n\
+                  for (var i = 0; i < 6; i = i + 1) print fib(i);";
+    let (output, result) = Lox::run_string_captured(source);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(output, "0\n1\n1\n2\n3\n5\n");
+}
+
+#[test]
+fn compile_errors() {
+    let result = Lox::run_string("print 1;\nvar = 2;");
+
+    assert_eq!(
+        result,
+        Err(vec![Diagnostic {
+            line: 2,
+            column: 5,
+            info: " at '='".to_string(),
+            msg: "Expect variable name.".to_string(),
+            severity: Severity::Error,
+        }])
+    );
+}
+
+#[test]
+fn runtime_error_keeps_earlier_output() {
+    let (output, result) = Lox::run_string_captured("print \"before\";\n-nil;");
+
+    assert_eq!(output, "before\n");
+    assert_eq!(
+        result,
+        Err(vec![Diagnostic::runtime(2, "Operand must be a number.")])
+    );
+}
+
+#[test]
+fn warnings_do_not_fail_the_run() {
+    let mut lox = Lox::new();
+
+    assert_eq!(lox.run_collecting("{ var a = 1; }"), Ok(()));
+    assert_eq!(
+        lox.take_errors(),
+        vec![Diagnostic {
+            line: 1,
+            column: 7,
+            info: String::new(),
+            msg: "Local variable 'a' is never used.".to_string(),
+            severity: Severity::Warning,
+        }]
+    );
+}
+
+#[test]
+fn interpreter_options() {
+    let mut lox = Lox::new().with_options(InterpreterOptions {
+        strict_division: true,
+        max_depth: 10,
+    });
+
+    assert_eq!(
+        lox.run_collecting("print 1 / 0;"),
+        Err(vec![Diagnostic::runtime(1, "Division by zero.")])
+    );
+    assert_eq!(
+        lox.run_collecting("fun f(n) { if (n > 0) f(n - 1); } f(5);\nf(20);"),
+        Err(vec![Diagnostic::runtime(1, "Stack overflow.")])
+    );
+}
+
+#[test]
+fn scanner_options() {
+    let options = ScannerOptions {
+        case_insensitive_keywords: true,
+        extra_keywords: [("show", TokenKind::Print)].into(),
+    };
+    let mut lox = Lox::new().with_scanner_options(options);
+
+    assert_eq!(lox.run_collecting("PRINT 1; show 2;"), Ok(()));
+    assert_eq!(
+        Lox::run_string("show 2;").unwrap_err()[0].msg,
+        "Expect ';' after expression."
+    );
+}
+
+#[test]
+fn extra_keywords_without_a_literal() {
+    let options = ScannerOptions {
+        extra_keywords: [("one", TokenKind::Number)].into(),
+        ..ScannerOptions::default()
+    };
+    let mut lox = Lox::new().with_scanner_options(options);
+
+    assert_eq!(
+        lox.run_collecting("print one;"),
+        Err(vec![Diagnostic {
+            line: 1,
+            column: 7,
+            info: " at 'one'".to_string(),
+            msg: "Expect expression.".to_string(),
+            severity: Severity::Error,
+        }])
+    );
+    assert_eq!(keywords().get("print"), Some(&TokenKind::Print));
+}
+
+#[test]
+fn stream_tokens() {
+    let mut errors = vec![];
+    let mut scanner = Scanner::new(&mut errors, "print @ 1;");
+    let first = scanner.next_token().unwrap();
+    let kinds: Vec<TokenKind> = scanner.map(|token| token.kind).collect();
+
+    assert_eq!(first.kind, TokenKind::Print);
+    assert_eq!(
+        kinds,
+        vec![TokenKind::Number, TokenKind::Semicolon, TokenKind::Eof]
+    );
+    assert_eq!(errors[0].msg, "Unexpected character '@'.");
+}
+
+#[test]
+fn unbounded_recursion_is_a_runtime_error() {
+    assert_eq!(
+        Lox::run_string("fun f() { f(); }\nf();"),
+        Err(vec![Diagnostic::runtime(1, "Stack overflow.")])
+    );
+}